@@ -1,11 +1,13 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 use anyhow::{Context, Result};
-use degiro_rs::types::{DegiroClient, Order};
+use degiro_rs::{
+    credentials::EnvCredentials,
+    types::{DegiroClient, Order},
+};
 use dotenvy::dotenv;
 use log::LevelFilter;
 use simple_logger::SimpleLogger;
-use std::env;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -15,16 +17,9 @@ async fn main() -> Result<()> {
         .init()
         .unwrap();
 
-    let username = env::var("DEGIRO_USERNAME").expect("username environment variable not set.");
     // Note to self: dotenv interprets certain chars as shell variables! escape them
-    let password = env::var("DEGIRO_PASSWORD").expect("password environment variable not set.");
-    let totp_secret =
-        env::var("DEGIRO_TOTP_SECRET").expect("totp secret environment variable not set.");
-
-    let mut client = DegiroClient::builder()
-        .username(username)
-        .password(password)
-        .totp_secret(totp_secret)
+    let client = DegiroClient::builder()
+        .credentials(EnvCredentials::default())
         .log_level(LevelFilter::Debug)
         .finalize();
 
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use crate::{
+    error::DegiroError,
+    types::{OrderCheck, PlacedOrder},
+};
+
+/// How [`crate::types::DegiroClient::submit_orders`] handles a batch.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Confirm nothing when a single order check fails or the cash doesn't cover the batch
+    pub abort_on_failed_check: bool,
+    /// Pause between two order confirmations
+    pub confirm_interval: Duration,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            abort_on_failed_check: true,
+            confirm_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// What happened to one order of a batch.
+#[derive(Debug)]
+pub enum OrderOutcome {
+    Placed(PlacedOrder),
+    CheckFailed(DegiroError),
+    /// Checked fine, but the batch was aborted before confirming it
+    NotConfirmed(OrderCheck),
+    ConfirmFailed {
+        check: OrderCheck,
+        error: DegiroError,
+    },
+}
+
+impl OrderOutcome {
+    pub fn placed(&self) -> Option<&PlacedOrder> {
+        match self {
+            OrderOutcome::Placed(placed) => Some(placed),
+            _ => None,
+        }
+    }
+}
+
+/// Result of [`crate::types::DegiroClient::submit_orders`], with the outcomes in
+/// the same order as the submitted orders.
+#[derive(Debug)]
+pub struct BatchReport {
+    pub outcomes: Vec<OrderOutcome>,
+    /// Sum of the transaction fees of all successful checks, in the base currency
+    pub total_fees: f64,
+    /// Value of the buy orders plus all fees in the base currency of the account,
+    /// see [`crate::types::Order::notional`] and [`crate::types::AccountInfo::fx_rate`].
+    /// Orders without any price or exchange rate to go by are not included.
+    pub required_cash: f64,
+    /// Free space in the base currency before the batch, as far as it can be
+    /// told from the checks of the buy orders
+    pub available_cash: Option<f64>,
+    pub aborted: bool,
+}
+
+impl BatchReport {
+    pub fn placed(&self) -> impl Iterator<Item = &PlacedOrder> {
+        self.outcomes.iter().filter_map(OrderOutcome::placed)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| !matches!(outcome, OrderOutcome::Placed(_)))
+            .count()
+    }
+}
@@ -0,0 +1,135 @@
+use jiff::{Span, Timestamp, civil::DateTime, tz::TimeZone};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::DegiroError;
+
+type Result<T> = std::result::Result<T, DegiroError>;
+
+/// How far back [`crate::types::DegiroClient::get_price_history`] goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartPeriod {
+    OneDay,
+    OneWeek,
+    OneMonth,
+    ThreeMonths,
+    SixMonths,
+    OneYear,
+    ThreeYears,
+    FiveYears,
+    FiftyYears,
+}
+
+impl ChartPeriod {
+    pub(crate) fn as_iso8601(self) -> &'static str {
+        match self {
+            ChartPeriod::OneDay => "P1D",
+            ChartPeriod::OneWeek => "P1W",
+            ChartPeriod::OneMonth => "P1M",
+            ChartPeriod::ThreeMonths => "P3M",
+            ChartPeriod::SixMonths => "P6M",
+            ChartPeriod::OneYear => "P1Y",
+            ChartPeriod::ThreeYears => "P3Y",
+            ChartPeriod::FiveYears => "P5Y",
+            ChartPeriod::FiftyYears => "P50Y",
+        }
+    }
+}
+
+/// Time covered by a single [`Candle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartResolution {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    OneHour,
+    OneDay,
+    OneWeek,
+    OneMonth,
+}
+
+impl ChartResolution {
+    pub(crate) fn as_iso8601(self) -> &'static str {
+        match self {
+            ChartResolution::OneMinute => "PT1M",
+            ChartResolution::FiveMinutes => "PT5M",
+            ChartResolution::FifteenMinutes => "PT15M",
+            ChartResolution::ThirtyMinutes => "PT30M",
+            ChartResolution::OneHour => "PT1H",
+            ChartResolution::OneDay => "P1D",
+            ChartResolution::OneWeek => "P7D",
+            ChartResolution::OneMonth => "P1M",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    /// Start of the interval
+    pub time: Timestamp,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ChartResponse {
+    #[serde(default)]
+    pub(crate) series: Vec<ChartSeries>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ChartSeries {
+    pub(crate) id: String,
+    /// `start/resolution`, e.g. `2025-05-01T00:00:00/P1D`
+    pub(crate) times: Option<String>,
+    #[serde(default)]
+    pub(crate) data: Vec<Vec<Value>>,
+}
+
+impl ChartSeries {
+    /// The rows are `[offset, open, high, low, close]`, where the offset counts
+    /// resolutions since the start in `times`. Rows with gaps are left out.
+    pub(crate) fn candles(&self) -> Result<Vec<Candle>> {
+        if self.data.is_empty() {
+            return Ok(Vec::new());
+        }
+        let invalid = || DegiroError::UnexpectedResponse(format!("chart times {:?}", self.times));
+        let (start, resolution) = self
+            .times
+            .as_deref()
+            .and_then(|times| times.split_once('/'))
+            .ok_or_else(invalid)?;
+        let resolution: Span = resolution.parse().map_err(|_| invalid())?;
+        // Requested in UTC, so the start carries no offset of its own
+        let start = start.parse::<DateTime>()?.to_zoned(TimeZone::UTC)?;
+
+        self.data
+            .iter()
+            .filter_map(|row| match row.as_slice() {
+                [offset, open, high, low, close] => Some((
+                    offset.as_i64()?,
+                    [
+                        open.as_f64()?,
+                        high.as_f64()?,
+                        low.as_f64()?,
+                        close.as_f64()?,
+                    ],
+                )),
+                _ => None,
+            })
+            .map(|(offset, [open, high, low, close])| {
+                let time = start.checked_add(resolution.checked_mul(offset)?)?;
+                Ok(Candle {
+                    time: time.timestamp(),
+                    open,
+                    high,
+                    low,
+                    close,
+                })
+            })
+            .collect()
+    }
+}
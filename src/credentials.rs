@@ -0,0 +1,212 @@
+use std::{collections::HashMap, fmt, fs, path::PathBuf, process::Command};
+
+use secrecy::{SecretString, zeroize::Zeroizing};
+
+use crate::error::DegiroError;
+
+type Result<T> = std::result::Result<T, DegiroError>;
+
+/// Login credentials, only materialised by a [`CredentialProvider`] when logging in.
+/// The secrets are zeroed on drop and redacted in `Debug` output.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: SecretString,
+    pub totp_secret: Option<SecretString>,
+}
+
+impl Credentials {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: SecretString::from(password.into()),
+            totp_secret: None,
+        }
+    }
+
+    pub fn with_totp_secret(self, totp_secret: impl Into<String>) -> Self {
+        Self {
+            totp_secret: Some(SecretString::from(totp_secret.into())),
+            ..self
+        }
+    }
+}
+
+/// Source of the [`Credentials`] used by [`crate::types::DegiroClient`],
+/// asked again on every login so nothing has to be kept around in between.
+/// The client calls it on tokio's blocking thread pool, so it may block.
+///
+/// Implemented for [`Credentials`] itself, the providers in this module and any
+/// `Fn() -> Result<Credentials, DegiroError>` closure.
+pub trait CredentialProvider: Send + Sync {
+    fn credentials(&self) -> Result<Credentials>;
+}
+
+impl fmt::Debug for dyn CredentialProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CredentialProvider")
+    }
+}
+
+impl CredentialProvider for Credentials {
+    fn credentials(&self) -> Result<Credentials> {
+        Ok(self.clone())
+    }
+}
+
+impl<F> CredentialProvider for F
+where
+    F: Fn() -> Result<Credentials> + Send + Sync,
+{
+    fn credentials(&self) -> Result<Credentials> {
+        self()
+    }
+}
+
+/// Reads the credentials from environment variables, by default the
+/// `DEGIRO_USERNAME`, `DEGIRO_PASSWORD` and `DEGIRO_TOTP_SECRET` ones from `.env.example`.
+/// The TOTP secret is optional.
+#[derive(Debug, Clone)]
+pub struct EnvCredentials {
+    pub username_var: String,
+    pub password_var: String,
+    pub totp_secret_var: String,
+}
+
+impl Default for EnvCredentials {
+    fn default() -> Self {
+        Self {
+            username_var: "DEGIRO_USERNAME".to_string(),
+            password_var: "DEGIRO_PASSWORD".to_string(),
+            totp_secret_var: "DEGIRO_TOTP_SECRET".to_string(),
+        }
+    }
+}
+
+impl CredentialProvider for EnvCredentials {
+    fn credentials(&self) -> Result<Credentials> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|e| DegiroError::Credentials(format!("{name}: {e}")))
+        };
+
+        let credentials = Credentials::new(var(&self.username_var)?, var(&self.password_var)?);
+        Ok(match std::env::var(&self.totp_secret_var) {
+            Ok(totp_secret) if !totp_secret.is_empty() => credentials.with_totp_secret(totp_secret),
+            _ => credentials,
+        })
+    }
+}
+
+/// Reads `KEY=value` lines using the same keys as [`EnvCredentials::default`],
+/// so a `.env` style file works as is.
+#[derive(Debug, Clone)]
+pub struct FileCredentials {
+    pub path: PathBuf,
+}
+
+impl FileCredentials {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CredentialProvider for FileCredentials {
+    fn credentials(&self) -> Result<Credentials> {
+        let contents = fs::read_to_string(&self.path)
+            .map(Zeroizing::new)
+            .map_err(|e| DegiroError::Credentials(format!("{}: {e}", self.path.display())))?;
+
+        let mut values: HashMap<&str, String> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim().to_string()))
+            .collect();
+
+        let mut take = |key: &str| {
+            values
+                .remove(key)
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| {
+                    DegiroError::Credentials(format!("{key} missing in {}", self.path.display()))
+                })
+        };
+
+        let credentials = Credentials::new(take("DEGIRO_USERNAME")?, take("DEGIRO_PASSWORD")?);
+        Ok(match take("DEGIRO_TOTP_SECRET") {
+            Ok(totp_secret) => credentials.with_totp_secret(totp_secret),
+            Err(_) => credentials,
+        })
+    }
+}
+
+/// Gets the secrets from the first line a command prints, e.g. `pass show degiro`.
+#[derive(Debug, Clone)]
+pub struct CommandCredentials {
+    pub username: String,
+    pub password_command: Vec<String>,
+    pub totp_secret_command: Option<Vec<String>>,
+}
+
+impl CommandCredentials {
+    pub fn new<I, S>(username: impl Into<String>, password_command: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            username: username.into(),
+            password_command: password_command.into_iter().map(Into::into).collect(),
+            totp_secret_command: None,
+        }
+    }
+
+    pub fn with_totp_secret_command<I, S>(self, totp_secret_command: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            totp_secret_command: Some(totp_secret_command.into_iter().map(Into::into).collect()),
+            ..self
+        }
+    }
+
+    fn run(command: &[String]) -> Result<SecretString> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| DegiroError::Credentials("empty command".to_string()))?;
+
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| DegiroError::Credentials(format!("{program}: {e}")))?;
+        if !output.status.success() {
+            return Err(DegiroError::Credentials(format!(
+                "{program} exited with {}",
+                output.status
+            )));
+        }
+
+        let stdout = String::from_utf8(output.stdout)
+            .map(Zeroizing::new)
+            .map_err(|_| DegiroError::Credentials(format!("{program} printed invalid UTF-8")))?;
+        let secret = stdout.lines().next().unwrap_or_default().to_string();
+        Ok(SecretString::from(secret))
+    }
+}
+
+impl CredentialProvider for CommandCredentials {
+    fn credentials(&self) -> Result<Credentials> {
+        Ok(Credentials {
+            username: self.username.clone(),
+            password: Self::run(&self.password_command)?,
+            totp_secret: self
+                .totp_secret_command
+                .as_deref()
+                .map(Self::run)
+                .transpose()?,
+        })
+    }
+}
@@ -1,8 +1,11 @@
-use std::time::SystemTimeError;
+use std::time::{Duration, SystemTimeError};
 
 use reqwest::StatusCode;
 use thiserror::Error;
 
+use crate::risk::RiskRule;
+use crate::types::{LoginStatus, OrderAction, OrderTimeType, OrderType};
+
 #[derive(Error, Debug)]
 pub enum DegiroError {
     #[error("network error: {0}")]
@@ -11,18 +14,119 @@ pub enum DegiroError {
     #[error("TOTP generation failed: {0}")]
     Totp(#[from] totp_rs::TotpUrlError),
 
+    #[error("could not get credentials: {0}")]
+    Credentials(String),
+
+    #[error("no TOTP secret configured")]
+    MissingTotpSecret,
+
     #[error("invalid TOTP secret encoding")]
     InvalidTotpSecret,
 
+    #[error("bad credentials ({remaining_attempts:?} attempts remaining)")]
+    BadCredentials {
+        remaining_attempts: Option<u32>,
+        login_failures: Option<u32>,
+        captcha_required: bool,
+    },
+
+    #[error("captcha required, log in through the web interface first")]
+    CaptchaRequired {
+        remaining_attempts: Option<u32>,
+        login_failures: Option<u32>,
+    },
+
+    #[error("account locked after {login_failures:?} failed logins")]
+    AccountLocked { login_failures: Option<u32> },
+
+    #[error("login requires a one-time password")]
+    TotpRequired,
+
+    #[error("login requires confirmation in the DEGIRO app")]
+    InAppConfirmationRequired,
+
+    #[error("login was not confirmed in the DEGIRO app within {0:?}")]
+    InAppConfirmationTimeout(Duration),
+
+    #[error("login failed with status {status:?}: {status_text}")]
+    LoginFailed {
+        status: LoginStatus,
+        status_text: String,
+        remaining_attempts: Option<u32>,
+        login_failures: Option<u32>,
+        captcha_required: bool,
+    },
+
     #[error("unexpected response: {0}")]
     UnexpectedResponse(String),
 
+    #[error("invalid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("session expired")]
+    SessionExpired,
+
     #[error("missing required session ID")]
     MissingSessionId,
 
     #[error("missing required int account")]
     MissingIntAccount,
 
+    #[error("product {0} can't be traded")]
+    ProductNotTradable(String),
+
+    #[error("product {product_id} doesn't support {order_type:?} orders to {action:?}")]
+    OrderTypeNotSupported {
+        product_id: String,
+        action: OrderAction,
+        order_type: OrderType,
+    },
+
+    #[error("{0:?} orders can't be built yet")]
+    OrderTypeNotBuildable(OrderType),
+
+    #[error("product {product_id} doesn't support {time_type:?} orders")]
+    TimeTypeNotSupported {
+        product_id: String,
+        time_type: OrderTimeType,
+    },
+
+    #[error("{order_type:?} orders need a {field}")]
+    MissingOrderField {
+        field: &'static str,
+        order_type: OrderType,
+    },
+
+    #[error("{order_type:?} orders don't take a {field}")]
+    UnexpectedOrderField {
+        field: &'static str,
+        order_type: OrderType,
+    },
+
+    #[error("invalid {field} {value}")]
+    InvalidOrderValue { field: &'static str, value: f64 },
+
+    #[error("risk limit: {0}")]
+    RiskLimit(RiskRule),
+
+    #[error("order check {confirmation_id} was not approved")]
+    OrderNotApproved { confirmation_id: String },
+
+    #[error("order {0} is no longer open or can't be modified")]
+    OrderNotModifiable(String),
+
+    #[error("order {order_id} was rejected: {}", reasons.join(", "))]
+    OrderRejected {
+        order_id: String,
+        reasons: Vec<String>,
+    },
+
+    #[error("product {0} has no vwd id to get prices for")]
+    MissingVwdId(String),
+
+    #[error("chart response without the {0} series")]
+    MissingChartSeries(String),
+
     #[error("HTTP error: {status} - {body}")]
     HttpError { status: StatusCode, body: String },
 
@@ -1,42 +1,101 @@
 use crate::types::{
-    CheckOrderResponse, ClientResponse, DegiroClient, FavoritesResponse, HistoryResponse, Order,
-    OrderConfirmationResponse, PortfolioResponse, ProductInfo, ProductInfoResponse,
-    ProductSearchResponse, TotpLoginRequest, TotpLoginResponse, TransactionsHistoryResponse,
+    CheckOrderResponse, ClientResponse, DegiroClient, FavoritesResponse, HistoryResponse,
+    InAppLoginRequest, LoginRequest, LoginResponse, LoginStatus, OpenOrder, Order, OrderAction,
+    OrderChanges, OrderCheck, OrderConfirmation, OrderConfirmationResponse, OrderErrorResponse,
+    OrderUpdate, OrderUpdateResponse, PlacedOrder, Portfolio, PortfolioResponse, Position,
+    PositionType, ProductInfo, ProductInfoResponse, ProductSearchResponse, TotalPortfolio,
+    TotalPortfolioSection, TotpLoginRequest, TransactionsHistoryResponse, UpdateRequest,
+    UpdateResponse,
 };
+use futures::Stream;
+use jiff::Timestamp;
 use jiff::civil::Date;
 use log::LevelFilter;
-use reqwest::{Client, RequestBuilder};
+use reqwest::{
+    Client, ClientBuilder, RequestBuilder, Response, StatusCode, Url,
+    cookie::{CookieStore, Jar},
+};
+use secrecy::ExposeSecret;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLockReadGuard, RwLockWriteGuard},
+    time::Duration,
+};
+use tokio::{sync::oneshot, time::Instant};
 use totp_rs::{Algorithm, Secret, TOTP};
 
+pub mod batch;
+pub mod chart;
+pub mod credentials;
 pub mod error;
+pub mod order;
+pub mod portfolio;
+pub mod quotecast;
+pub mod risk;
 pub mod types;
+pub mod watch;
 
+use batch::{BatchOptions, BatchReport, OrderOutcome};
+use chart::{Candle, ChartPeriod, ChartResolution, ChartResponse};
+use credentials::Credentials;
 use error::DegiroError;
-use types::{AccountInfo, AccountInfoResponse, AccountOverview, AccountOverviewResponse};
+use portfolio::PortfolioTracker;
+use quotecast::Quotecast;
+use risk::RiskRule;
+use types::{
+    AccountInfo, AccountInfoResponse, AccountOverview, AccountOverviewResponse, ClientConfig,
+    ClientConfigResponse, ClientProfile, KeepAlive, ReloginPolicy, Replay, Service, Session,
+    SessionState,
+};
+use watch::{WatchEvent, WatchOptions, Watcher};
 
 type Result<T> = std::result::Result<T, DegiroError>;
 
+pub(crate) const DEFAULT_BASE_URL: &str = "https://trader.degiro.nl";
+
+const IN_APP_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub(crate) fn http_client_builder(cookie_jar: &Arc<Jar>, log_level: LevelFilter) -> ClientBuilder {
+    let connection_verbose = log_level <= LevelFilter::Debug;
+
+    Client::builder()
+        .cookie_provider(cookie_jar.clone())
+        .user_agent(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:138.0) Gecko/20100101 Firefox/138.0",
+        )
+        .connection_verbose(connection_verbose)
+}
+
 impl DegiroClient {
     pub fn finalize(self) -> Result<Self> {
-        let connection_verbose = self.log_level <= LevelFilter::Debug;
-
-        let client = Client::builder()
-            .cookie_store(true)
-            .user_agent(
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:138.0) Gecko/20100101 Firefox/138.0",
-            )
-            .connection_verbose(connection_verbose)
-            .build()?;
+        let client = http_client_builder(&self.cookie_jar, self.log_level).build()?;
 
         Ok(Self { client, ..self })
     }
 
-    fn session_and_account(&self) -> Result<(&str, u64)> {
-        let session_id = self
+    fn session(&self) -> RwLockReadGuard<'_, Session> {
+        self.session.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn session_mut(&self) -> RwLockWriteGuard<'_, Session> {
+        self.session.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn session_id(&self) -> Result<String> {
+        self.session()
             .session_id
-            .as_deref()
+            .clone()
+            .ok_or(DegiroError::MissingSessionId)
+    }
+
+    fn session_and_account(&self) -> Result<(String, u64)> {
+        let session = self.session();
+        let session_id = session
+            .session_id
+            .clone()
             .ok_or(DegiroError::MissingSessionId)?;
-        let int_account = self.int_account.ok_or(DegiroError::MissingIntAccount)?;
+        let int_account = session.int_account.ok_or(DegiroError::MissingIntAccount)?;
 
         Ok((session_id, int_account))
     }
@@ -56,112 +115,675 @@ impl DegiroClient {
             .header("Referer", "https://trader.degiro.nl/trader/")
     }
 
-    pub async fn login_with_totp(&mut self) -> Result<()> {
-        let url = format!("{}/login/secure/login/totp", self.base_url);
+    fn build_put(&self, url: &str) -> RequestBuilder {
+        self.client
+            .put(url)
+            .header("Accept", "application/json, text/plain, */*")
+            .header("Content-Type", "application/json; charset=UTF-8")
+            .header("Referer", "https://trader.degiro.nl/trader/")
+    }
+
+    fn build_delete(&self, url: &str) -> RequestBuilder {
+        self.client
+            .delete(url)
+            .header("Accept", "application/json, text/plain, */*")
+            .header("Referer", "https://trader.degiro.nl/trader/")
+    }
+
+    /// Sends the request `build` produces for the current session id and int account.
+    /// If DEGIRO reports the session as expired the configured login flow is run
+    /// once and the request is rebuilt and replayed, as far as `relogin_policy`
+    /// allows it for this kind of request. Requests that may not be replayed still
+    /// get a fresh session for whatever comes next, but fail with the expired one.
+    async fn send_authenticated<F>(&self, replay: Replay, build: F) -> Result<Response>
+    where
+        F: Fn(&str, u64) -> RequestBuilder,
+    {
+        let (session_id, int_account) = self.session_and_account()?;
+        let response = build(&session_id, int_account).send().await?;
+
+        if response.status() != StatusCode::UNAUTHORIZED
+            || self.relogin_policy == ReloginPolicy::Never
+        {
+            return Ok(response);
+        }
+
+        log::info!("session expired, logging in again");
+        self.relogin(&session_id).await?;
+
+        if !self.relogin_policy.allows(replay) {
+            log::warn!("not replaying a {replay:?} request after logging in again");
+            return Ok(response);
+        }
 
+        let (session_id, int_account) = self.session_and_account()?;
+        Ok(build(&session_id, int_account).send().await?)
+    }
+
+    async fn request_json<T, F>(&self, replay: Replay, build: F) -> Result<T>
+    where
+        T: DeserializeOwned,
+        F: Fn(&str, u64) -> RequestBuilder,
+    {
+        let response = self.send_authenticated(replay, build).await?;
+        Self::parse_response(response).await
+    }
+
+    async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T> {
+        match response.status() {
+            StatusCode::UNAUTHORIZED => Err(DegiroError::SessionExpired),
+            status if !status.is_success() => Err(DegiroError::HttpError {
+                status,
+                body: response.text().await?,
+            }),
+            _ => Ok(response.json().await?),
+        }
+    }
+
+    /// Concurrent requests can all run into the same expired session,
+    /// only the first one to get here actually logs in again.
+    async fn relogin(&self, expired_session_id: &str) -> Result<()> {
+        let _guard = self.login_lock.lock().await;
+
+        if self.session().session_id.as_deref() != Some(expired_session_id) {
+            return Ok(());
+        }
+        self.login().await
+    }
+
+    /// Logs in with username and password only. When DEGIRO asks for a one-time
+    /// password this falls through to [`Self::login_with_totp`] if a TOTP secret is
+    /// configured, otherwise [`DegiroError::TotpRequired`] is returned and the caller
+    /// can retry with [`Self::login_with_one_time_password`].
+    ///
+    /// Accounts using the app's push confirmation only get a session once the login
+    /// is approved on the phone, set `in_app_confirmation_timeout` to wait for that.
+    pub async fn login(&self) -> Result<()> {
+        let credentials = self.load_credentials().await?;
+        let url = format!("{}/login/secure/login", self.base_url);
+
+        let payload = LoginRequest {
+            username: &credentials.username,
+            password: credentials.password.expose_secret(),
+            is_pass_code_reset: false,
+            is_redirect_to_mobile: false,
+            query_params: serde_json::json!({}),
+        };
+
+        let login_response = self.send_login(&url, &payload).await?;
+        match (login_response.status, self.in_app_confirmation_timeout) {
+            (LoginStatus::TotpNeeded, _) if credentials.totp_secret.is_some() => {
+                self.totp_login(&credentials).await
+            }
+            (LoginStatus::InAppConfirmationNeeded, Some(timeout)) => {
+                let in_app_token = login_response.in_app_token.ok_or_else(|| {
+                    DegiroError::UnexpectedResponse("missing inAppToken".to_string())
+                })?;
+                self.wait_for_in_app_confirmation(&credentials, in_app_token, timeout)
+                    .await
+            }
+            _ => {
+                let session_id = Self::login_session_id(login_response)?;
+                self.start_session(session_id).await
+            }
+        }
+    }
+
+    /// Polls until the login is approved in the DEGIRO app, or `timeout` runs out.
+    async fn wait_for_in_app_confirmation(
+        &self,
+        credentials: &Credentials,
+        in_app_token: String,
+        timeout: Duration,
+    ) -> Result<()> {
+        let url = format!("{}/login/secure/login/in-app", self.base_url);
+        let deadline = Instant::now() + timeout;
+
+        let payload = InAppLoginRequest {
+            username: &credentials.username,
+            password: credentials.password.expose_secret(),
+            in_app_token,
+            query_params: serde_json::json!({}),
+        };
+
+        loop {
+            let login_response = self.send_login(&url, &payload).await?;
+            if login_response.status != LoginStatus::InAppConfirmationNeeded {
+                let session_id = Self::login_session_id(login_response)?;
+                return self.start_session(session_id).await;
+            }
+
+            if Instant::now() + IN_APP_POLL_INTERVAL > deadline {
+                return Err(DegiroError::InAppConfirmationTimeout(timeout));
+            }
+            tokio::time::sleep(IN_APP_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Providers may block, e.g. to run `pass`, so they don't get to hold up an async
+    /// worker, let alone every request waiting on `login_lock`.
+    async fn load_credentials(&self) -> Result<Credentials> {
+        let provider = Arc::clone(&self.credentials);
+        tokio::task::spawn_blocking(move || provider.credentials())
+            .await
+            .map_err(|e| DegiroError::Credentials(format!("credential provider failed: {e}")))?
+    }
+
+    pub async fn login_with_totp(&self) -> Result<()> {
+        let credentials = self.load_credentials().await?;
+        self.totp_login(&credentials).await
+    }
+
+    async fn totp_login(&self, credentials: &Credentials) -> Result<()> {
+        let totp_secret = credentials
+            .totp_secret
+            .as_ref()
+            .ok_or(DegiroError::MissingTotpSecret)?;
+
+        // totp-rs zeroes both the encoded and the decoded secret on drop
+        let secret = Secret::Encoded(totp_secret.expose_secret().to_string());
         let totp = TOTP::new(
             Algorithm::SHA1,
             6,
             1,
             30,
-            Secret::Encoded(self.totp_secret.clone())
+            secret
                 .to_bytes()
                 .map_err(|_| DegiroError::InvalidTotpSecret)?,
         )?;
         let totp_token = totp.generate_current()?;
 
+        self.one_time_password_login(credentials, &totp_token).await
+    }
+
+    /// For users who type the 6-digit code from their authenticator app
+    /// instead of handing the TOTP secret to the client.
+    pub async fn login_with_one_time_password(&self, code: &str) -> Result<()> {
+        let credentials = self.load_credentials().await?;
+        self.one_time_password_login(&credentials, code).await
+    }
+
+    async fn one_time_password_login(&self, credentials: &Credentials, code: &str) -> Result<()> {
+        let url = format!("{}/login/secure/login/totp", self.base_url);
+
         let totp_payload = TotpLoginRequest {
-            username: self.username.clone(),
-            password: self.password.clone(),
+            username: &credentials.username,
+            password: credentials.password.expose_secret(),
             query_params: serde_json::json!({}),
-            one_time_password: totp_token,
+            one_time_password: code.to_string(),
             save_device: false,
         };
 
+        let login_response = self.send_login(&url, &totp_payload).await?;
+
+        let session_id = Self::login_session_id(login_response)?;
+        self.start_session(session_id).await
+    }
+
+    async fn send_login<T: Serialize>(&self, url: &str, payload: &T) -> Result<LoginResponse> {
         let req = self
-            .build_post(&url)
+            .build_post(url)
             .header("Origin", "https://trader.degiro.nl")
             .header("Content-Type", "application/json;charset=UTF-8")
             .header("Referer", "https://trader.degiro.nl/login/nl")
-            .json(&totp_payload)
+            .json(payload)
             .build()?;
 
         let res = self.client.execute(req).await?;
-        // TODO: handle errors properly
-        // if !res.status().is_success() {
-        //     return Err(DegiroError::LoginFailed(
-        //         res.status().to_string(),
-        //         res.text().await?,
-        //     ));
-        // }
-        let totp_response: TotpLoginResponse = res.json().await?;
-
-        self.session_id = Some(totp_response.session_id.clone());
+        Self::parse_login_response(res).await
+    }
+
+    /// DEGIRO answers failed logins with a JSON body describing the failure
+    /// (usually alongside a 4xx), so try to read that before giving up on the status.
+    async fn parse_login_response(res: reqwest::Response) -> Result<LoginResponse> {
+        let status = res.status();
+        let body = res.text().await?;
+
+        match serde_json::from_str::<LoginResponse>(&body) {
+            Ok(login_response) => Ok(login_response),
+            Err(_) if !status.is_success() => Err(DegiroError::HttpError { status, body }),
+            Err(e) => Err(DegiroError::UnexpectedResponse(e.to_string())),
+        }
+    }
+
+    /// `captchaRequired` can come with any status, so it is passed on in the
+    /// error rather than replacing the status.
+    fn login_session_id(res: LoginResponse) -> Result<String> {
+        match res.status {
+            LoginStatus::Success => res.session_id.ok_or(DegiroError::MissingSessionId),
+            LoginStatus::BadCredentials => Err(DegiroError::BadCredentials {
+                remaining_attempts: res.remaining_attempts,
+                login_failures: res.login_failures,
+                captcha_required: res.captcha_required,
+            }),
+            LoginStatus::CaptchaRequired => Err(DegiroError::CaptchaRequired {
+                remaining_attempts: res.remaining_attempts,
+                login_failures: res.login_failures,
+            }),
+            LoginStatus::AccountLocked => Err(DegiroError::AccountLocked {
+                login_failures: res.login_failures,
+            }),
+            LoginStatus::TotpNeeded => Err(DegiroError::TotpRequired),
+            LoginStatus::InAppConfirmationNeeded => Err(DegiroError::InAppConfirmationRequired),
+            LoginStatus::Unknown(_) => Err(DegiroError::LoginFailed {
+                status: res.status,
+                status_text: res.status_text.unwrap_or_default(),
+                remaining_attempts: res.remaining_attempts,
+                login_failures: res.login_failures,
+                captcha_required: res.captcha_required,
+            }),
+        }
+    }
+
+    async fn start_session(&self, session_id: String) -> Result<()> {
+        {
+            let mut session = self.session_mut();
+            session.session_id = Some(session_id);
+            session.logged_in_at = Some(Timestamp::now());
+        }
+        self.get_client_config().await?;
         self.get_int_account().await
     }
 
-    pub async fn get_int_account(&mut self) -> Result<()> {
-        let url = format!(
-            "{}/pa/secure/client?sessionId={}",
-            self.base_url,
-            self.session_id
-                .as_deref()
-                .ok_or(DegiroError::MissingSessionId)?
-        );
+    /// Snapshot of the current session, to be stored and handed to
+    /// [`Self::resume_or_login`] on the next start instead of logging in again.
+    pub fn session_state(&self) -> Result<SessionState> {
+        let (session_id, int_account) = self.session_and_account()?;
+        let url = self.base_url()?;
 
-        let response = self.build_get(&url).send().await?;
-        let client_response: ClientResponse = response.json().await?;
-        self.int_account = Some(client_response.data.int_account);
+        let cookies = self
+            .cookie_jar
+            .cookies(&url)
+            .and_then(|header| header.to_str().map(str::to_string).ok())
+            .map(|header| header.split("; ").map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(SessionState {
+            session_id,
+            int_account,
+            cookies,
+            logged_in_at: self.session().logged_in_at.unwrap_or_else(Timestamp::now),
+        })
+    }
+
+    /// Puts a previously exported session in place without checking it is still valid.
+    pub fn restore_session(&self, state: SessionState) -> Result<()> {
+        let url = self.base_url()?;
+        for cookie in &state.cookies {
+            self.cookie_jar.add_cookie_str(cookie, &url);
+        }
+
+        *self.session_mut() = Session {
+            session_id: Some(state.session_id),
+            int_account: Some(state.int_account),
+            logged_in_at: Some(state.logged_in_at),
+            ..Session::default()
+        };
 
         Ok(())
     }
 
-    pub async fn get_favorites(&self) -> Result<Vec<u64>> {
-        let (session_id, int_account) = self.session_and_account()?;
+    /// Restores `state` and probes it with a cheap authenticated call,
+    /// doing a full [`Self::login`] only if DEGIRO no longer accepts it.
+    /// Other failures of the probe, like a timeout or a 5xx, are returned
+    /// as they are rather than spending a login on them.
+    pub async fn resume_or_login(&self, state: SessionState) -> Result<()> {
+        self.restore_session(state)?;
+
+        match self.get_int_account().await {
+            Ok(()) => self.get_client_config().await.map(|_| ()),
+            Err(DegiroError::SessionExpired) => {
+                log::info!("restored session is no longer valid, logging in again");
+                *self.session_mut() = Session::default();
+                self.login().await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn base_url(&self) -> Result<Url> {
+        Url::parse(&self.base_url).map_err(|e| DegiroError::InvalidUrl(e.to_string()))
+    }
+
+    /// Spawns a task that calls a cheap authenticated endpoint every `interval` so
+    /// the session doesn't time out while idle. Expiry is handled like for any
+    /// other request, the task only gives up once the session can't be recovered
+    /// and then reports why through [`KeepAlive::session_lost`].
+    ///
+    /// The task stops when the returned [`KeepAlive`] is dropped.
+    pub fn keep_alive(&self, interval: Duration) -> KeepAlive {
+        let client = self.clone();
+        let (tx, session_lost) = oneshot::channel();
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                match client.ping().await {
+                    Ok(()) => {}
+                    Err(DegiroError::Reqwest(e)) => {
+                        log::warn!("keep-alive request failed, retrying next tick: {e}");
+                    }
+                    Err(e) => {
+                        let _ = tx.send(e);
+                        return;
+                    }
+                }
+            }
+        });
+
+        KeepAlive { session_lost, task }
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.get_client_profile().await.map(|_| ())
+    }
+
+    /// Fetches the service URLs DEGIRO hands out for this session, every
+    /// request made afterwards is routed through them.
+    pub async fn get_client_config(&self) -> Result<ClientConfig> {
+        let url = format!("{}/login/secure/config", self.base_url);
+
+        let response = self.build_get(&url).send().await?;
+        let config_response: ClientConfigResponse = Self::parse_response(response).await?;
+        self.session_mut().config = Some(config_response.data.clone());
+
+        Ok(config_response.data)
+    }
+
+    /// The config fetched at login, `None` until then.
+    pub fn client_config(&self) -> Option<ClientConfig> {
+        self.session().config.clone()
+    }
+
+    /// Root URL of `service` without trailing slash. Taken from the client config
+    /// when there is one, unless `base_url` was changed, which then takes precedence
+    /// so tests can point everything at a mock server.
+    pub(crate) fn service_url(&self, service: Service) -> String {
+        if self.base_url == DEFAULT_BASE_URL {
+            if let Some(url) = service.external_url() {
+                return url.to_string();
+            }
+            let session = self.session();
+            let configured = session
+                .config
+                .as_ref()
+                .and_then(|config| service.configured_url(config));
+            if let Some(url) = configured {
+                return url.trim_end_matches('/').to_string();
+            }
+        }
+
+        format!("{}/{}", self.base_url, service.default_path())
+    }
+
+    pub async fn get_int_account(&self) -> Result<()> {
         let url = format!(
-            "https://trader.degiro.nl/favorites/secure/v1?intAccount={}&sessionId={}",
-            int_account, session_id
+            "{}/client?sessionId={}",
+            self.service_url(Service::Pa),
+            self.session_id()?
         );
 
         let response = self.build_get(&url).send().await?;
-        let fav_response: FavoritesResponse = response.json().await?;
+        let client_response: ClientResponse = Self::parse_response(response).await?;
+        self.session_mut().int_account = Some(client_response.data.int_account);
+
+        Ok(())
+    }
+
+    /// Fetches the full profile of the client behind the login.
+    pub async fn get_client_profile(&self) -> Result<ClientProfile> {
+        let client_response: ClientResponse = self
+            .request_json(Replay::Allowed, |session_id, _| {
+                let url = format!(
+                    "{}/client?sessionId={}",
+                    self.service_url(Service::Pa),
+                    session_id
+                );
+                self.build_get(&url)
+            })
+            .await?;
+
+        Ok(client_response.data)
+    }
+
+    pub async fn get_favorites(&self) -> Result<Vec<u64>> {
+        let fav_response: FavoritesResponse = self
+            .request_json(Replay::Allowed, |session_id, int_account| {
+                let url = format!(
+                    "{}/secure/v1?intAccount={}&sessionId={}",
+                    self.service_url(Service::Favorites),
+                    int_account,
+                    session_id
+                );
+                self.build_get(&url)
+            })
+            .await?;
         Ok(fav_response.data.first().unwrap().product_ids.clone())
     }
 
     pub async fn get_products_details(&self, ids: Vec<String>) -> Result<Vec<ProductInfo>> {
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = format!(
-            "https://trader.degiro.nl/product_search/secure/v5/products/info?intAccount={}&sessionId={}",
-            int_account, session_id
-        );
-
-        let response = self.build_post(&url).json(&ids).send().await?;
-        let product_info: ProductInfoResponse = response.json().await?;
+        let product_info: ProductInfoResponse = self
+            .request_json(Replay::Allowed, |session_id, int_account| {
+                let url = format!(
+                    "{}/v5/products/info?intAccount={}&sessionId={}",
+                    self.service_url(Service::ProductSearch),
+                    int_account,
+                    session_id
+                );
+                self.build_post(&url).json(&ids)
+            })
+            .await?;
         Ok(product_info.data.into_values().collect())
     }
 
     pub async fn search_product_by_name(&self, name: &str) -> Result<Vec<ProductInfo>> {
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = format!(
-            "https://trader.degiro.nl/product_search/secure/v5/products/lookup?offset=0&limit=10&searchText={}&intAccount={}&sessionId={}",
-            name, int_account, session_id
-        );
-
-        let response = self.build_get(&url).send().await?;
-        let found_products: ProductSearchResponse = response.json().await?;
+        let found_products: ProductSearchResponse = self
+            .request_json(Replay::Allowed, |session_id, int_account| {
+                let url = format!(
+                    "{}/v5/products/lookup?offset=0&limit=10&searchText={}&intAccount={}&sessionId={}",
+                    self.service_url(Service::ProductSearch),
+                    name,
+                    int_account,
+                    session_id
+                );
+                self.build_get(&url)
+            })
+            .await?;
         Ok(found_products.products)
     }
 
     pub async fn get_portfolio(&self) -> Result<PortfolioResponse> {
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = format!(
-            "https://trader.degiro.nl/trading/secure/v5/update/{};jsessionid={}?intAccount={}&jsessionId={}&portfolio=0",
-            int_account, session_id, int_account, session_id
+        let portfolio = self.get_portfolio_since(0).await?.ok_or_else(|| {
+            DegiroError::UnexpectedResponse("update without portfolio".to_string())
+        })?;
+        Ok(PortfolioResponse { portfolio })
+    }
+
+    /// Only the rows that changed after `last_updated`, as returned in
+    /// [`Portfolio::last_updated`] by the previous call. `0` gets the full portfolio,
+    /// `None` means nothing changed. See [`PortfolioTracker`] for applying these.
+    pub async fn get_portfolio_since(&self, last_updated: u64) -> Result<Option<Portfolio>> {
+        let request = UpdateRequest {
+            portfolio: Some(last_updated),
+            ..Default::default()
+        };
+        Ok(self.poll_update(&request).await?.portfolio)
+    }
+
+    /// Cash, margin and value of the account in the `base_currency` of [`Self::get_account_info`].
+    pub async fn get_total_portfolio(&self) -> Result<TotalPortfolio> {
+        let request = UpdateRequest {
+            portfolio: Some(0),
+            total_portfolio: Some(0),
+            ..Default::default()
+        };
+        let res = self.poll_update(&request).await?;
+
+        let section = res.total_portfolio.ok_or_else(|| {
+            DegiroError::UnexpectedResponse("update without totalPortfolio".to_string())
+        })?;
+        let positions: Vec<Position> = res
+            .portfolio
+            .map(|portfolio| portfolio.value.iter().map(Position::from).collect())
+            .unwrap_or_default();
+        self.total_portfolio(&section, &positions, &mut HashMap::new())
+            .await
+    }
+
+    /// Builds the [`TotalPortfolio`] with today's P&L converted to the base
+    /// currency. `currencies` caches the currency of each product id, so
+    /// product details are only fetched for positions not seen before.
+    pub(crate) async fn total_portfolio(
+        &self,
+        section: &TotalPortfolioSection,
+        positions: &[Position],
+        currencies: &mut HashMap<String, String>,
+    ) -> Result<TotalPortfolio> {
+        let account_info = self.get_account_info().await?;
+
+        let unknown: Vec<String> = positions
+            .iter()
+            .filter(|position| position.position_type == PositionType::Product)
+            .filter(|position| !currencies.contains_key(&position.product_id))
+            .map(|position| position.product_id.clone())
+            .collect();
+        if !unknown.is_empty() {
+            for product in self.get_products_details(unknown).await? {
+                currencies.insert(product.id, product.currency);
+            }
+        }
+
+        let fx_rates: HashMap<String, f64> = positions
+            .iter()
+            .filter_map(|position| {
+                let currency = currencies.get(&position.product_id)?;
+                Some((position.product_id.clone(), account_info.fx_rate(currency)?))
+            })
+            .collect();
+        Ok(TotalPortfolio::new(
+            section,
+            account_info.base_currency,
+            positions,
+            &fx_rates,
+        ))
+    }
+
+    /// Orders that are placed but not yet filled, cancelled or expired.
+    pub async fn get_open_orders(&self) -> Result<Vec<OpenOrder>> {
+        let request = UpdateRequest {
+            orders: Some(0),
+            ..Default::default()
+        };
+        Ok(self
+            .poll_update(&request)
+            .await?
+            .orders
+            .map(|orders| orders.open_orders())
+            .unwrap_or_default())
+    }
+
+    /// Gets all sections selected in `request` in one go. Pass
+    /// [`UpdateRequest::next`] to the following call to only get what changed.
+    pub async fn poll_update(&self, request: &UpdateRequest) -> Result<UpdateResponse> {
+        let sections = request.query();
+        self.request_json(Replay::Allowed, |session_id, int_account| {
+            let url = format!(
+                "{}/v5/update/{};jsessionid={}",
+                self.service_url(Service::Trading),
+                int_account,
+                session_id
+            );
+            self.build_get(&url)
+                .query(&[
+                    ("intAccount", int_account.to_string()),
+                    ("jsessionId", session_id.to_string()),
+                ])
+                .query(&sections)
+        })
+        .await
+    }
+
+    /// Polls the update endpoint and reports what changed as a stream. Errors
+    /// are passed on, reading on after one polls again.
+    pub fn watch(
+        &self,
+        options: WatchOptions,
+    ) -> impl Stream<Item = Result<WatchEvent>> + Send + 'static {
+        Watcher::new(self.clone(), options).into_stream()
+    }
+
+    /// Opens a quotecast session for live prices of the products' `vwd_id`s.
+    pub async fn quotecast(&self) -> Result<Quotecast> {
+        Quotecast::connect(
+            self.client.clone(),
+            self.service_url(Service::VwdQuotecast),
+            self.user_token().await?,
+        )
+        .await
+    }
+
+    /// Open, high, low and close prices of `product` over `period`, one candle per `resolution`.
+    pub async fn get_price_history(
+        &self,
+        product: &ProductInfo,
+        period: ChartPeriod,
+        resolution: ChartResolution,
+    ) -> Result<Vec<Candle>> {
+        let vwd_id = product
+            .vwd_id
+            .as_deref()
+            .ok_or_else(|| DegiroError::MissingVwdId(product.id.clone()))?;
+        let series_id = format!(
+            "ohlc:{}:{}",
+            product.vwd_identifier_type.as_deref().unwrap_or("issueid"),
+            vwd_id
         );
 
-        let response = self.build_get(&url).send().await?;
-        let res: PortfolioResponse = response.json().await?;
-        Ok(res)
+        let url = format!("{}/data.js", self.service_url(Service::Chart));
+        let response = self
+            .build_get(&url)
+            .query(&[
+                ("requestid", "1"),
+                ("format", "json"),
+                ("culture", "en-US"),
+                ("tz", "UTC"),
+                ("period", period.as_iso8601()),
+                ("resolution", resolution.as_iso8601()),
+                ("series", &series_id),
+                ("userToken", &self.user_token().await?.to_string()),
+            ])
+            .send()
+            .await?;
+        let chart: ChartResponse = Self::parse_response(response).await?;
+
+        chart
+            .series
+            .iter()
+            .find(|series| series.id == series_id)
+            .ok_or(DegiroError::MissingChartSeries(series_id))?
+            .candles()
+    }
+
+    /// The client id, which vwd wants as `userToken`
+    async fn user_token(&self) -> Result<u64> {
+        let config = match self.client_config() {
+            Some(config) => config,
+            None => self.get_client_config().await?,
+        };
+        config
+            .client_id
+            .ok_or_else(|| DegiroError::UnexpectedResponse("config without clientId".to_string()))
+    }
+
+    pub fn portfolio_tracker(&self) -> PortfolioTracker {
+        PortfolioTracker::new(self.clone())
+    }
+
+    pub async fn get_positions(&self) -> Result<Vec<Position>> {
+        Ok(self.get_portfolio().await?.positions())
     }
 
     pub async fn get_order_history(
@@ -172,65 +794,394 @@ impl DegiroClient {
         let from_date: Date = from_date_raw.parse()?;
         let to_date: Date = to_date_raw.parse()?;
 
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = "https://trader.degiro.nl/portfolio-reports/secure/v4/order-history";
-        let params = [
-            ("fromDate", from_date.to_string()),
-            ("toDate", to_date.to_string()),
-            ("intAccount", int_account.to_string()),
-            ("sessionId", session_id.to_string()),
-        ];
-
-        let response = self.build_get(url).query(&params).send().await?;
-        let res: HistoryResponse = response.json().await?;
-        Ok(res)
+        self.request_json(Replay::Allowed, |session_id, int_account| {
+            let url = format!(
+                "{}/v4/order-history",
+                self.service_url(Service::PortfolioReports)
+            );
+            let params = [
+                ("fromDate", from_date.to_string()),
+                ("toDate", to_date.to_string()),
+                ("intAccount", int_account.to_string()),
+                ("sessionId", session_id.to_string()),
+            ];
+            self.build_get(&url).query(&params)
+        })
+        .await
     }
 
     pub async fn check_order(&self, order: &Order) -> Result<CheckOrderResponse> {
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = format!(
-            "https://trader.degiro.nl/trading/secure/v5/checkOrder;jsessionid={}",
-            session_id
-        );
+        self.request_json(Replay::Allowed, |session_id, int_account| {
+            let url = format!(
+                "{}/v5/checkOrder;jsessionid={}",
+                self.service_url(Service::Trading),
+                session_id
+            );
+            self.build_post(&url)
+                .query(&[
+                    ("intAccount", int_account.to_string()),
+                    ("sessionId", session_id.to_string()),
+                ])
+                .json(order)
+        })
+        .await
+    }
 
-        let response = self
-            .build_post(&url)
-            .query(&[
-                ("intAccount", int_account.to_string()),
-                ("sessionId", session_id.to_string()),
-            ])
-            .json(order)
-            .send()
-            .await?;
+    /// Checks the order and only confirms it when `approve` accepts the fees, taxes
+    /// and free space of the [`OrderCheck`]. A declined check results in
+    /// [`DegiroError::OrderNotApproved`] and nothing is placed.
+    pub async fn place_order<F>(&self, order: &Order, approve: F) -> Result<PlacedOrder>
+    where
+        F: FnOnce(&OrderCheck) -> bool,
+    {
+        let check = self.check_order(order).await?.data;
+        if !approve(&check) {
+            return Err(DegiroError::OrderNotApproved {
+                confirmation_id: check.confirmation_id,
+            });
+        }
+
+        let confirmation = self
+            .confirm_order(&check.confirmation_id, order)
+            .await?
+            .data;
+        Ok(PlacedOrder {
+            order_id: confirmation.order_id.clone(),
+            check,
+            confirmation,
+        })
+    }
+
+    /// Checks all `orders` first and then confirms them one after the other,
+    /// `confirm_interval` apart. Failing orders end up in the report instead of
+    /// failing the whole batch.
+    pub async fn submit_orders(&self, orders: Vec<Order>, options: &BatchOptions) -> BatchReport {
+        let mut checks = Vec::with_capacity(orders.len());
+        for order in &orders {
+            checks.push(self.check_order(order).await.map(|res| res.data));
+        }
+
+        let mut product_ids: Vec<String> = orders.iter().map(|o| o.product_id.clone()).collect();
+        product_ids.sort();
+        product_ids.dedup();
+        let products: HashMap<String, ProductInfo> =
+            match self.get_products_details(product_ids).await {
+                Ok(products) => products.into_iter().map(|p| (p.id.clone(), p)).collect(),
+                Err(e) => {
+                    log::warn!("no product details, leaving order values out of the batch: {e}");
+                    HashMap::new()
+                }
+            };
+        // Fees and free space are in the base currency, order values in the product's
+        let account_info = match self.get_account_info().await {
+            Ok(account_info) => Some(account_info),
+            Err(e) => {
+                log::warn!("no exchange rates, leaving order values out of the batch: {e}");
+                None
+            }
+        };
+        let notional = |order: &Order| {
+            let product = products.get(&order.product_id)?;
+            let fx_rate = account_info.as_ref()?.fx_rate(&product.currency);
+            if fx_rate.is_none() {
+                log::warn!("no exchange rate for {}, leaving it out", product.currency);
+            }
+            Some(order.notional(product)? * fx_rate?)
+        };
+
+        let mut total_fees = 0.0;
+        let mut required_cash = 0.0;
+        let mut available_cash: Option<f64> = None;
+        for (order, check) in orders.iter().zip(&checks) {
+            let Ok(check) = check else { continue };
+            let fee = check.transaction_fee.unwrap_or_default();
+            total_fees += fee;
+            required_cash += fee;
+
+            if order.buy_sell == OrderAction::Buy
+                && let Some(notional) = notional(order)
+            {
+                required_cash += notional;
+                // The check tells the free space as if only this order was placed
+                if let Some(free_space) = check.free_space_new {
+                    let before = free_space + notional + fee;
+                    available_cash = Some(available_cash.map_or(before, |a| a.max(before)));
+                }
+            }
+        }
+
+        let short_of_cash = available_cash.is_some_and(|available| required_cash > available);
+        if short_of_cash {
+            log::warn!("batch needs {required_cash} but only {available_cash:?} is available");
+        }
+        let aborted =
+            options.abort_on_failed_check && (short_of_cash || checks.iter().any(Result::is_err));
+
+        let mut outcomes = Vec::with_capacity(orders.len());
+        let mut confirmed_any = false;
+        for (order, check) in orders.iter().zip(checks) {
+            let check = match check {
+                Ok(check) if aborted => {
+                    outcomes.push(OrderOutcome::NotConfirmed(check));
+                    continue;
+                }
+                Ok(check) => check,
+                Err(error) => {
+                    outcomes.push(OrderOutcome::CheckFailed(error));
+                    continue;
+                }
+            };
 
-        let res: CheckOrderResponse = response.json().await?;
-        Ok(res)
+            if confirmed_any {
+                tokio::time::sleep(options.confirm_interval).await;
+            }
+            confirmed_any = true;
+
+            outcomes.push(
+                match self.confirm_order(&check.confirmation_id, order).await {
+                    Ok(res) => OrderOutcome::Placed(PlacedOrder {
+                        order_id: res.data.order_id.clone(),
+                        check,
+                        confirmation: res.data,
+                    }),
+                    Err(error) => OrderOutcome::ConfirmFailed { check, error },
+                },
+            );
+        }
+
+        BatchReport {
+            outcomes,
+            total_fees,
+            required_cash,
+            available_cash,
+            aborted,
+        }
     }
 
-    // TODO: test this :)
     pub async fn confirm_order(
         &self,
         confirmation_id: &str,
         order: &Order,
     ) -> Result<OrderConfirmationResponse> {
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = format!(
-            "https://trader.degiro.nl/trading/secure/v5/order/{};jsessionid={}",
-            confirmation_id, session_id
-        );
+        let notional = self.check_risk(order, None).await?;
+
+        if self.dry_run {
+            // Held to the daily limit like a real order, but never counted towards it
+            self.risk_state.release(notional);
+            log::info!("dry run, not confirming {confirmation_id}: {order:?}");
+            return Ok(OrderConfirmationResponse {
+                data: OrderConfirmation {
+                    order_id: format!("dry-run-{confirmation_id}"),
+                    response_datetime: None,
+                    request_duration: None,
+                },
+            });
+        }
+
+        let result = self
+            .request_json(Replay::OrderConfirmation, |session_id, int_account| {
+                let url = self.order_url(confirmation_id, session_id);
+                self.build_post(&url)
+                    .query(&[
+                        ("intAccount", int_account.to_string()),
+                        ("sessionId", session_id.to_string()),
+                    ])
+                    .json(order)
+            })
+            .await;
+        if Self::surely_rejected(&result) {
+            self.risk_state.release(notional);
+        }
+        result
+    }
+
+    /// Whether DEGIRO surely turned an order or change down, a timeout or a
+    /// garbled answer may well belong to one that went through. Server errors
+    /// are just as ambiguous when they come from a proxy.
+    fn surely_rejected<T>(result: &Result<T>) -> bool {
+        match result {
+            Err(DegiroError::SessionExpired | DegiroError::OrderRejected { .. }) => true,
+            Err(DegiroError::HttpError { status, .. }) => status.is_client_error(),
+            _ => false,
+        }
+    }
+
+    /// Runs `order` past the [`risk::RiskLimits`] and counts its value in the
+    /// base currency towards today's total. When modifying, `replaces` is the
+    /// order as it was and only the increase is counted. Returns what was
+    /// counted, to be released again if the order doesn't go through.
+    async fn check_risk(&self, order: &Order, replaces: Option<&Order>) -> Result<f64> {
+        if self.risk_state.kill_switch() {
+            return Err(DegiroError::RiskLimit(RiskRule::KillSwitch));
+        }
+
+        let limits = &self.risk_limits;
+        limits.check_product(order)?;
+
+        let product = if limits.needs_product() {
+            self.get_products_details(vec![order.product_id.clone()])
+                .await?
+                .into_iter()
+                .find(|product| product.id == order.product_id)
+        } else {
+            None
+        };
+        let fx_rate = match &product {
+            Some(product) if limits.needs_notional() => {
+                self.get_account_info().await?.fx_rate(&product.currency)
+            }
+            _ => None,
+        };
+        let in_base = |order: &Order| Some(order.notional(product.as_ref()?)? * fx_rate?);
+        let notional = in_base(order);
+        limits.check_order(notional)?;
+
+        if limits.max_position_size.is_some() {
+            let current_size = self
+                .get_positions()
+                .await?
+                .iter()
+                .find(|position| position.product_id == order.product_id)
+                .map_or(0.0, |position| position.size);
+            limits.check_position(order, product.as_ref(), current_size)?;
+        }
+
+        let previous = replaces.and_then(in_base).unwrap_or_default();
+        let counted = (notional.unwrap_or_default() - previous).max(0.0);
+        self.risk_state
+            .reserve(counted, limits.max_daily_notional)?;
+        Ok(counted)
+    }
+
+    /// Rejects every order confirmation and modification until released,
+    /// for all clones of this client. Cancelling orders keeps working.
+    pub fn engage_kill_switch(&self) {
+        log::warn!("kill switch engaged");
+        self.risk_state.set_kill_switch(true);
+    }
+
+    pub fn release_kill_switch(&self) {
+        self.risk_state.set_kill_switch(false);
+    }
+
+    pub fn is_kill_switch_engaged(&self) -> bool {
+        self.risk_state.kill_switch()
+    }
+
+    /// Changes a pending order, looked up through [`Self::get_open_orders`] since DEGIRO
+    /// wants the complete order again. Orders that were filled or cancelled in the
+    /// meantime fail with [`DegiroError::OrderNotModifiable`], and so do trailing stop
+    /// and amount orders, which can only be cancelled and placed again.
+    pub async fn modify_order(
+        &self,
+        order_id: &str,
+        changes: &OrderChanges,
+    ) -> Result<OrderUpdate> {
+        let open_order = self
+            .get_open_orders()
+            .await?
+            .into_iter()
+            .find(|order| order.order_id == order_id)
+            .filter(|order| order.is_modifiable)
+            .ok_or_else(|| DegiroError::OrderNotModifiable(order_id.to_string()))?;
+
+        let original = Order::try_from(&open_order)?;
+        let mut order = original.clone();
+        changes.apply(&mut order);
+        let increase = self.check_risk(&order, Some(&original)).await?;
+
+        if self.dry_run {
+            self.risk_state.release(increase);
+            log::info!("dry run, not modifying {order_id}: {order:?}");
+            return Ok(Self::dry_run_update(order_id));
+        }
+
+        let result = async {
+            let response = self
+                .send_authenticated(Replay::Allowed, |session_id, int_account| {
+                    let url = self.order_url(order_id, session_id);
+                    self.build_put(&url)
+                        .query(&[
+                            ("intAccount", int_account.to_string()),
+                            ("sessionId", session_id.to_string()),
+                        ])
+                        .json(&order)
+                })
+                .await?;
+            Self::parse_order_update(order_id, response).await
+        }
+        .await;
+        if Self::surely_rejected(&result) {
+            self.risk_state.release(increase);
+        }
+        result
+    }
+
+    pub async fn cancel_order(&self, order_id: &str) -> Result<OrderUpdate> {
+        if self.dry_run {
+            log::info!("dry run, not cancelling {order_id}");
+            return Ok(Self::dry_run_update(order_id));
+        }
 
         let response = self
-            .build_post(&url)
-            .query(&[
-                ("intAccount", int_account.to_string()),
-                ("sessionId", session_id.to_string()),
-            ])
-            .json(order)
-            .send()
+            .send_authenticated(Replay::Allowed, |session_id, int_account| {
+                let url = self.order_url(order_id, session_id);
+                self.build_delete(&url).query(&[
+                    ("intAccount", int_account.to_string()),
+                    ("sessionId", session_id.to_string()),
+                ])
+            })
             .await?;
+        Self::parse_order_update(order_id, response).await
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn dry_run_update(order_id: &str) -> OrderUpdate {
+        OrderUpdate {
+            order_id: order_id.to_string(),
+            response_datetime: None,
+        }
+    }
+
+    fn order_url(&self, order_id: &str, session_id: &str) -> String {
+        format!(
+            "{}/v5/order/{};jsessionid={}",
+            self.service_url(Service::Trading),
+            order_id,
+            session_id
+        )
+    }
 
-        let res: OrderConfirmationResponse = response.json().await?;
-        Ok(res)
+    async fn parse_order_update(order_id: &str, response: Response) -> Result<OrderUpdate> {
+        let status = response.status();
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(DegiroError::SessionExpired);
+        }
+
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(match serde_json::from_str::<OrderErrorResponse>(&body) {
+                Ok(res) => DegiroError::OrderRejected {
+                    order_id: order_id.to_string(),
+                    reasons: res.errors.into_iter().map(|e| e.text).collect(),
+                },
+                Err(_) => DegiroError::HttpError { status, body },
+            });
+        }
+
+        let res: OrderUpdateResponse = if body.trim().is_empty() {
+            OrderUpdateResponse::default()
+        } else {
+            serde_json::from_str(&body)
+                .map_err(|e| DegiroError::UnexpectedResponse(format!("order update: {e}")))?
+        };
+        Ok(OrderUpdate {
+            order_id: order_id.to_string(),
+            response_datetime: res.data.and_then(|data| data.response_datetime),
+        })
     }
 
     pub async fn get_transaction_history(
@@ -242,31 +1193,35 @@ impl DegiroClient {
         let from_date: Date = from_date_raw.parse()?;
         let to_date: Date = to_date_raw.parse()?;
 
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = "https://trader.degiro.nl/portfolio-reports/secure/v4/transactions";
-
-        let params = [
-            ("fromDate", from_date.to_string()),
-            ("toDate", to_date.to_string()),
-            ("groupTransactionsByOrder", aggregate_order.to_string()),
-            ("intAccount", int_account.to_string()),
-            ("sessionId", session_id.to_string()),
-        ];
-
-        let response = self.build_get(url).query(&params).send().await?;
-        let json: TransactionsHistoryResponse = response.json().await?;
-        Ok(json)
+        self.request_json(Replay::Allowed, |session_id, int_account| {
+            let url = format!(
+                "{}/v4/transactions",
+                self.service_url(Service::PortfolioReports)
+            );
+            let params = [
+                ("fromDate", from_date.to_string()),
+                ("toDate", to_date.to_string()),
+                ("groupTransactionsByOrder", aggregate_order.to_string()),
+                ("intAccount", int_account.to_string()),
+                ("sessionId", session_id.to_string()),
+            ];
+            self.build_get(&url).query(&params)
+        })
+        .await
     }
 
     pub async fn get_account_info(&self) -> Result<AccountInfo> {
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = format!(
-            "https://trader.degiro.nl/trading/secure/v5/account/info/{};jsessionid={}",
-            int_account, session_id
-        );
-
-        let response = self.build_get(&url).send().await?;
-        let json: AccountInfoResponse = response.json().await?;
+        let json: AccountInfoResponse = self
+            .request_json(Replay::Allowed, |session_id, int_account| {
+                let url = format!(
+                    "{}/v5/account/info/{};jsessionid={}",
+                    self.service_url(Service::Trading),
+                    int_account,
+                    session_id
+                );
+                self.build_get(&url)
+            })
+            .await?;
 
         Ok(json.data)
     }
@@ -279,17 +1234,21 @@ impl DegiroClient {
         let from_date: Date = from_date_raw.parse()?;
         let to_date: Date = to_date_raw.parse()?;
 
-        let (session_id, int_account) = self.session_and_account()?;
-        let url = "https://trader.degiro.nl/portfolio-reports/secure/v6/accountoverview";
-        let params = [
-            ("fromDate", from_date.to_string()),
-            ("toDate", to_date.to_string()),
-            ("intAccount", int_account.to_string()),
-            ("sessionId", session_id.to_string()),
-        ];
-
-        let response = self.build_get(&url).query(&params).send().await?;
-        let json: AccountOverviewResponse = response.json().await?;
+        let json: AccountOverviewResponse = self
+            .request_json(Replay::Allowed, |session_id, int_account| {
+                let url = format!(
+                    "{}/v6/accountoverview",
+                    self.service_url(Service::PortfolioReports)
+                );
+                let params = [
+                    ("fromDate", from_date.to_string()),
+                    ("toDate", to_date.to_string()),
+                    ("intAccount", int_account.to_string()),
+                    ("sessionId", session_id.to_string()),
+                ];
+                self.build_get(&url).query(&params)
+            })
+            .await?;
 
         Ok(json.data)
     }
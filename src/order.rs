@@ -0,0 +1,141 @@
+use bon::bon;
+
+use crate::{
+    error::DegiroError,
+    types::{Order, OrderAction, OrderTimeType, OrderType, ProductInfo},
+};
+
+type Result<T> = std::result::Result<T, DegiroError>;
+
+#[bon]
+impl Order {
+    /// Builds an order that is checked against what `product` allows before
+    /// anything is sent to DEGIRO.
+    ///
+    /// ```no_run
+    /// # use degiro_rs::types::{Order, OrderAction, OrderType, ProductInfo};
+    /// # fn example(product: &ProductInfo) -> Result<(), degiro_rs::error::DegiroError> {
+    /// let order = Order::builder(product, OrderAction::Buy)
+    ///     .order_type(OrderType::Limit)
+    ///     .size(10.0)
+    ///     .price(12.5)
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[builder(builder_type = OrderBuilder, start_fn = builder, finish_fn = build)]
+    pub fn validated(
+        #[builder(start_fn)] product: &ProductInfo,
+        #[builder(start_fn)] action: OrderAction,
+        order_type: OrderType,
+        /// In contracts, so one option with a `contract_size` of 100 is `1.0`.
+        /// Not used for [`OrderType::StandardAmount`]
+        size: Option<f64>,
+        price: Option<f64>,
+        stop_price: Option<f64>,
+        amount: Option<f64>,
+        #[builder(default = OrderTimeType::GoodTillDay)] time_type: OrderTimeType,
+    ) -> Result<Order> {
+        if !product.tradable {
+            return Err(DegiroError::ProductNotTradable(product.id.clone()));
+        }
+        // There is no field for the trail yet, see `OrderType::TrailingStop`
+        if order_type == OrderType::TrailingStop {
+            return Err(DegiroError::OrderTypeNotBuildable(order_type));
+        }
+
+        let order_types = match action {
+            OrderAction::Buy => &product.buy_order_types,
+            OrderAction::Sell => &product.sell_order_types,
+        };
+        if !supports(order_types, order_type.product_info_name()) {
+            return Err(DegiroError::OrderTypeNotSupported {
+                product_id: product.id.clone(),
+                action,
+                order_type,
+            });
+        }
+        if !supports(&product.order_time_types, time_type.product_info_name()) {
+            return Err(DegiroError::TimeTypeNotSupported {
+                product_id: product.id.clone(),
+                time_type,
+            });
+        }
+
+        let by_amount = order_type == OrderType::StandardAmount;
+        let fields = Fields::for_type(order_type);
+        check_field("price", price, fields.price, order_type)?;
+        check_field("stop price", stop_price, fields.stop_price, order_type)?;
+        check_field("amount", amount, by_amount, order_type)?;
+        check_field("size", size, !by_amount, order_type)?;
+
+        for (field, value) in [
+            ("size", size),
+            ("price", price),
+            ("stop price", stop_price),
+            ("amount", amount),
+        ] {
+            if let Some(value) = value.filter(|v| !(v.is_finite() && *v > 0.0)) {
+                return Err(DegiroError::InvalidOrderValue { field, value });
+            }
+        }
+
+        Ok(Order {
+            buy_sell: action,
+            order_type,
+            product_id: product.id.clone(),
+            size: size.unwrap_or_default(),
+            price,
+            time_type,
+            stop_price,
+            amount,
+        })
+    }
+}
+
+/// Which of the optional order fields an order type needs, the others must be left out.
+struct Fields {
+    price: bool,
+    stop_price: bool,
+}
+
+impl Fields {
+    fn for_type(order_type: OrderType) -> Self {
+        let (price, stop_price) = match order_type {
+            OrderType::Limit => (true, false),
+            OrderType::StopLimit => (true, true),
+            OrderType::StopLoss => (false, true),
+            OrderType::Market
+            | OrderType::TrailingStop
+            | OrderType::StandardAmount
+            | OrderType::StandardSize => (false, false),
+            // Nothing to check against, so whatever was given is passed on
+            OrderType::Unknown => (true, true),
+        };
+        Fields { price, stop_price }
+    }
+}
+
+// Products without the metadata are left for DEGIRO to judge
+fn supports(supported: &Option<Vec<String>>, name: Option<&str>) -> bool {
+    match (supported, name) {
+        (None, _) => true,
+        (Some(supported), Some(name)) => supported.iter().any(|s| s == name),
+        (Some(_), None) => false,
+    }
+}
+
+fn check_field<T>(
+    field: &'static str,
+    value: Option<T>,
+    required: bool,
+    order_type: OrderType,
+) -> Result<()> {
+    match value {
+        None if required && order_type != OrderType::Unknown => {
+            Err(DegiroError::MissingOrderField { field, order_type })
+        }
+        Some(_) if !required => Err(DegiroError::UnexpectedOrderField { field, order_type }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::DegiroError,
+    types::{DegiroClient, Portfolio, Position, PositionRow, UpdateRow},
+};
+
+type Result<T> = std::result::Result<T, DegiroError>;
+
+/// Keeps a local copy of the portfolio up to date by only asking DEGIRO
+/// for what changed since the previous poll.
+#[derive(Debug)]
+pub struct PortfolioTracker {
+    client: DegiroClient,
+    last_updated: u64,
+    rows: BTreeMap<String, PositionRow>,
+}
+
+/// What a single [`PortfolioTracker::poll`] changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PortfolioDiff {
+    pub added: Vec<Position>,
+    /// The positions as they are after the change
+    pub changed: Vec<Position>,
+    /// Ids of the positions that are gone
+    pub removed: Vec<String>,
+}
+
+impl PortfolioDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl PortfolioTracker {
+    pub fn new(client: DegiroClient) -> Self {
+        Self {
+            client,
+            last_updated: 0,
+            rows: BTreeMap::new(),
+        }
+    }
+
+    /// The first poll fetches the whole portfolio and reports every position as added.
+    pub async fn poll(&mut self) -> Result<PortfolioDiff> {
+        match self.client.get_portfolio_since(self.last_updated).await? {
+            Some(portfolio) => Ok(self.apply(portfolio)),
+            None => Ok(PortfolioDiff::default()),
+        }
+    }
+
+    /// Merges an update from [`DegiroClient::get_portfolio_since`] into the local copy.
+    pub fn apply(&mut self, portfolio: Portfolio) -> PortfolioDiff {
+        let changes = merge_rows(&mut self.rows, portfolio.value);
+        self.last_updated = portfolio.last_updated;
+
+        let position = |id: &String| Position::from(&self.rows[id]);
+        PortfolioDiff {
+            added: changes.added.iter().map(position).collect(),
+            changed: changes.changed.iter().map(position).collect(),
+            removed: changes.removed.into_iter().map(|row| row.id).collect(),
+        }
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.rows.values().map(Position::from).collect()
+    }
+
+    pub fn position(&self, product_id: &str) -> Option<Position> {
+        self.rows.get(product_id).map(Position::from)
+    }
+
+    /// The counter sent with the next poll, `0` before the first one.
+    pub fn last_updated(&self) -> u64 {
+        self.last_updated
+    }
+}
+
+/// Ids of the rows [`merge_rows`] touched, removed rows are handed back whole.
+#[derive(Debug)]
+pub(crate) struct RowChanges<R> {
+    pub(crate) added: Vec<String>,
+    pub(crate) changed: Vec<String>,
+    pub(crate) removed: Vec<R>,
+}
+
+impl<R> Default for RowChanges<R> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            changed: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+/// Applies the rows of an incremental update section, where changed rows only
+/// carry the fields that changed.
+pub(crate) fn merge_rows<R: UpdateRow>(
+    rows: &mut BTreeMap<String, R>,
+    update: Vec<R>,
+) -> RowChanges<R> {
+    let mut changes = RowChanges::default();
+
+    for mut row in update {
+        let id = row.id().to_string();
+        if row.is_removed() {
+            if let Some(removed) = rows.remove(&id) {
+                changes.removed.push(removed);
+            }
+            continue;
+        }
+
+        match rows.get_mut(&id) {
+            Some(existing) if !row.is_added() => {
+                let fields = existing.fields_mut();
+                for field in std::mem::take(row.fields_mut()) {
+                    match fields.iter_mut().find(|f| f.name == field.name) {
+                        Some(existing_field) => *existing_field = field,
+                        None => fields.push(field),
+                    }
+                }
+                changes.changed.push(id);
+            }
+            _ => {
+                changes.added.push(id.clone());
+                rows.insert(id, row);
+            }
+        }
+    }
+
+    changes
+}
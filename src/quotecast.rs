@@ -0,0 +1,328 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use futures::{Stream, stream};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{error::DegiroError, types::DegiroClient};
+
+type Result<T> = std::result::Result<T, DegiroError>;
+
+// What the web trader sends when it requests a session
+const SESSION_VERSION: &str = "1.0.20170315";
+
+/// Latest known values of one product, as sent by vwd.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Quote {
+    pub vwd_id: String,
+    pub last_price: Option<f64>,
+    pub bid_price: Option<f64>,
+    pub ask_price: Option<f64>,
+    /// Traded today
+    pub volume: Option<f64>,
+    pub open_price: Option<f64>,
+    pub high_price: Option<f64>,
+    pub low_price: Option<f64>,
+    // TODO: find out the format, so far it's been a plain time of day
+    pub last_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    LastPrice,
+    BidPrice,
+    AskPrice,
+    Volume,
+    OpenPrice,
+    HighPrice,
+    LowPrice,
+    LastTime,
+}
+
+impl Metric {
+    const ALL: [Metric; 8] = [
+        Metric::LastPrice,
+        Metric::BidPrice,
+        Metric::AskPrice,
+        Metric::Volume,
+        Metric::OpenPrice,
+        Metric::HighPrice,
+        Metric::LowPrice,
+        Metric::LastTime,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Metric::LastPrice => "LastPrice",
+            Metric::BidPrice => "BidPrice",
+            Metric::AskPrice => "AskPrice",
+            Metric::Volume => "CumulativeVolume",
+            Metric::OpenPrice => "OpenPrice",
+            Metric::HighPrice => "HighPrice",
+            Metric::LowPrice => "LowPrice",
+            Metric::LastTime => "LastTime",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Metric::ALL.into_iter().find(|metric| metric.name() == name)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionResponse {
+    session_id: String,
+}
+
+/// A single entry of the compact wire format, e.g. `{"m":"un","v":[12,101.5]}`.
+#[derive(Debug, Deserialize)]
+struct Message {
+    m: String,
+    #[serde(default)]
+    v: Vec<Value>,
+}
+
+/// Turns quotecast messages into [`Quote`]s. vwd first announces a numeric
+/// reference for every subscribed `vwd_id.Metric` and then only sends that reference.
+#[derive(Debug, Default)]
+struct Decoder {
+    references: HashMap<u64, (String, Metric)>,
+    quotes: HashMap<String, Quote>,
+}
+
+/// The session is gone and has to be requested again, along with the subscriptions.
+#[derive(Debug)]
+struct SessionRestart;
+
+impl Decoder {
+    fn apply(&mut self, messages: Vec<Message>) -> std::result::Result<Vec<Quote>, SessionRestart> {
+        let mut updated: Vec<String> = Vec::new();
+
+        for message in messages {
+            match (message.m.as_str(), message.v.as_slice()) {
+                ("a_req", [Value::String(key), reference]) => {
+                    let parsed = key.rsplit_once('.').and_then(|(vwd_id, metric)| {
+                        Some((vwd_id.to_string(), Metric::from_name(metric)?))
+                    });
+                    if let (Some(reference), Some(parsed)) = (reference.as_u64(), parsed) {
+                        self.references.insert(reference, parsed);
+                    }
+                }
+                ("a_rel", [Value::String(key), ..]) => {
+                    self.references.retain(|_, (vwd_id, metric)| {
+                        *key != format!("{vwd_id}.{}", metric.name())
+                    });
+                }
+                ("un" | "us" | "ue", [reference, rest @ ..]) => {
+                    let Some((vwd_id, metric)) =
+                        reference.as_u64().and_then(|r| self.references.get(&r))
+                    else {
+                        continue;
+                    };
+                    let quote = self.quotes.entry(vwd_id.clone()).or_insert_with(|| Quote {
+                        vwd_id: vwd_id.clone(),
+                        ..Default::default()
+                    });
+                    set(quote, *metric, rest.first());
+                    if !updated.contains(vwd_id) {
+                        updated.push(vwd_id.clone());
+                    }
+                }
+                ("sr", _) => return Err(SessionRestart),
+                ("h", _) => {}
+                (other, _) => log::debug!("ignoring quotecast message {other}"),
+            }
+        }
+
+        Ok(updated
+            .into_iter()
+            .filter_map(|vwd_id| self.quotes.get(&vwd_id).cloned())
+            .collect())
+    }
+}
+
+fn set(quote: &mut Quote, metric: Metric, value: Option<&Value>) {
+    let number = value.and_then(|v| v.as_f64().or_else(|| v.as_str()?.parse().ok()));
+    match metric {
+        Metric::LastPrice => quote.last_price = number,
+        Metric::BidPrice => quote.bid_price = number,
+        Metric::AskPrice => quote.ask_price = number,
+        Metric::Volume => quote.volume = number,
+        Metric::OpenPrice => quote.open_price = number,
+        Metric::HighPrice => quote.high_price = number,
+        Metric::LowPrice => quote.low_price = number,
+        Metric::LastTime => {
+            quote.last_time = value.and_then(|v| match v {
+                Value::String(s) => Some(s.clone()),
+                Value::Null => None,
+                other => Some(other.to_string()),
+            })
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    session_id: Option<String>,
+    subscribed: BTreeSet<String>,
+}
+
+/// Live prices from vwd, the data provider behind the DEGIRO web trader.
+/// Cloning gives another handle to the same quotecast session, so one clone can
+/// read [`Self::quotes`] while another one changes the subscriptions.
+#[derive(Debug, Clone)]
+pub struct Quotecast {
+    client: Client,
+    url: String,
+    user_token: u64,
+    state: Arc<Mutex<State>>,
+}
+
+impl Quotecast {
+    /// Opens a quotecast session, `user_token` is the client id of the logged in user.
+    pub(crate) async fn connect(client: Client, url: String, user_token: u64) -> Result<Self> {
+        let quotecast = Quotecast {
+            client,
+            url,
+            user_token,
+            state: Arc::default(),
+        };
+        quotecast.session_id().await?;
+        Ok(quotecast)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    async fn session_id(&self) -> Result<String> {
+        if let Some(session_id) = self.state().session_id.clone() {
+            return Ok(session_id);
+        }
+
+        let url = format!("{}/request_session", self.url);
+        let response = self
+            .client
+            .post(&url)
+            .query(&[
+                ("version", SESSION_VERSION.to_string()),
+                ("userToken", self.user_token.to_string()),
+            ])
+            .json(&json!({ "referrer": "https://trader.degiro.nl" }))
+            .send()
+            .await?;
+        let session: SessionResponse = DegiroClient::parse_response(response).await?;
+
+        self.state().session_id = Some(session.session_id.clone());
+        Ok(session.session_id)
+    }
+
+    async fn control(&self, action: &str, vwd_ids: &[String]) -> Result<()> {
+        if vwd_ids.is_empty() {
+            return Ok(());
+        }
+
+        let control_data: String = vwd_ids
+            .iter()
+            .flat_map(|vwd_id| {
+                Metric::ALL
+                    .iter()
+                    .map(move |metric| format!("{action}({vwd_id}.{});", metric.name()))
+            })
+            .collect();
+
+        let url = format!("{}/{}", self.url, self.session_id().await?);
+        let response = self
+            .client
+            .post(&url)
+            .query(&[("userToken", self.user_token)])
+            .json(&json!({ "controlData": control_data }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(DegiroError::HttpError {
+                status: response.status(),
+                body: response.text().await?,
+            });
+        }
+        Ok(())
+    }
+
+    /// Starts sending quotes for the given `vwd_id`s of [`crate::types::ProductInfo`].
+    pub async fn subscribe<I, S>(&self, vwd_ids: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let vwd_ids: Vec<String> = vwd_ids.into_iter().map(Into::into).collect();
+        self.control("a_req", &vwd_ids).await?;
+        self.state().subscribed.extend(vwd_ids);
+        Ok(())
+    }
+
+    pub async fn unsubscribe<I, S>(&self, vwd_ids: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let vwd_ids: Vec<String> = vwd_ids.into_iter().map(Into::into).collect();
+        self.control("a_rel", &vwd_ids).await?;
+        let mut state = self.state();
+        for vwd_id in &vwd_ids {
+            state.subscribed.remove(vwd_id);
+        }
+        Ok(())
+    }
+
+    /// Long polls vwd and yields a [`Quote`] every time one of its values changes.
+    /// Expired sessions are renewed and subscribed to again on the fly.
+    pub fn quotes(&self) -> impl Stream<Item = Result<Quote>> + Send + 'static {
+        let initial = (self.clone(), Decoder::default(), VecDeque::new());
+        stream::unfold(
+            initial,
+            |(quotecast, mut decoder, mut pending)| async move {
+                loop {
+                    if let Some(quote) = pending.pop_front() {
+                        return Some((Ok(quote), (quotecast, decoder, pending)));
+                    }
+
+                    match quotecast.fetch(&mut decoder).await {
+                        Ok(quotes) => pending.extend(quotes),
+                        Err(e) => return Some((Err(e), (quotecast, decoder, pending))),
+                    }
+                }
+            },
+        )
+    }
+
+    async fn fetch(&self, decoder: &mut Decoder) -> Result<Vec<Quote>> {
+        let url = format!("{}/{}", self.url, self.session_id().await?);
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("userToken", self.user_token)])
+            .send()
+            .await?;
+        let messages: Vec<Message> = DegiroClient::parse_response(response).await?;
+
+        match decoder.apply(messages) {
+            Ok(quotes) => Ok(quotes),
+            Err(SessionRestart) => {
+                log::info!("quotecast session expired, requesting a new one");
+                decoder.references.clear();
+                let subscribed: Vec<String> = {
+                    let mut state = self.state();
+                    state.session_id = None;
+                    state.subscribed.iter().cloned().collect()
+                };
+                self.control("a_req", &subscribed).await?;
+                Ok(Vec::new())
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use jiff::{Zoned, civil::Date};
+
+use crate::{
+    error::DegiroError,
+    types::{Order, OrderAction, ProductInfo},
+};
+
+type Result<T> = std::result::Result<T, DegiroError>;
+
+/// Guards checked before an order is confirmed or modified. Amounts are in the
+/// base currency of the account, order values are converted with the rates of
+/// [`crate::types::AccountInfo::fx_rate`].
+#[derive(Debug, Clone, Default)]
+pub struct RiskLimits {
+    /// Largest value of a single order, see [`Order::notional`]
+    pub max_order_notional: Option<f64>,
+    /// Largest total of the orders confirmed today, counted by this client and its
+    /// clones. Modifications count with what they add to the order. Orders DEGIRO
+    /// rejected and dry runs don't count.
+    pub max_daily_notional: Option<f64>,
+    /// Only these products can be traded when set
+    pub allowed_products: Option<HashSet<String>>,
+    /// Largest size a position may reach, either long or short
+    pub max_position_size: Option<f64>,
+}
+
+/// The guard that stopped an order, see [`DegiroError::RiskLimit`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskRule {
+    KillSwitch,
+    ProductNotAllowed(String),
+    /// Orders without a price, or amount orders under a position limit,
+    /// for a product without a close price to go by. Also for products in a
+    /// currency the account has no exchange rate for.
+    UnknownNotional,
+    OrderNotional {
+        notional: f64,
+        limit: f64,
+    },
+    DailyNotional {
+        total: f64,
+        limit: f64,
+    },
+    PositionSize {
+        size: f64,
+        limit: f64,
+    },
+}
+
+impl fmt::Display for RiskRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRule::KillSwitch => write!(f, "kill switch is engaged"),
+            RiskRule::ProductNotAllowed(product_id) => {
+                write!(f, "product {product_id} is not allowed")
+            }
+            RiskRule::UnknownNotional => write!(f, "order value can't be determined"),
+            RiskRule::OrderNotional { notional, limit } => {
+                write!(f, "order value {notional} is over {limit}")
+            }
+            RiskRule::DailyNotional { total, limit } => {
+                write!(f, "daily order value {total} would be over {limit}")
+            }
+            RiskRule::PositionSize { size, limit } => {
+                write!(f, "position size {size} would be over {limit}")
+            }
+        }
+    }
+}
+
+/// Shared between clones of a client, so the kill switch and daily total are global.
+#[derive(Debug, Default)]
+pub(crate) struct RiskState {
+    kill_switch: AtomicBool,
+    daily_notional: Mutex<Option<(Date, f64)>>,
+}
+
+impl RiskState {
+    pub(crate) fn set_kill_switch(&self, engaged: bool) {
+        self.kill_switch.store(engaged, Ordering::SeqCst);
+    }
+
+    pub(crate) fn kill_switch(&self) -> bool {
+        self.kill_switch.load(Ordering::SeqCst)
+    }
+
+    /// Adds `notional` to today's total when that stays within `limit`.
+    pub(crate) fn reserve(&self, notional: f64, limit: Option<f64>) -> Result<()> {
+        let today = Zoned::now().date();
+        let mut daily = self
+            .daily_notional
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let spent = match *daily {
+            Some((date, spent)) if date == today => spent,
+            _ => 0.0,
+        };
+
+        let total = spent + notional;
+        if let Some(limit) = limit.filter(|limit| total > *limit) {
+            return Err(DegiroError::RiskLimit(RiskRule::DailyNotional {
+                total,
+                limit,
+            }));
+        }
+        *daily = Some((today, total));
+        Ok(())
+    }
+
+    /// Undoes a [`Self::reserve`] for an order that didn't go through.
+    pub(crate) fn release(&self, notional: f64) {
+        let mut daily = self
+            .daily_notional
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, spent)) = daily.as_mut() {
+            *spent = (*spent - notional).max(0.0);
+        }
+    }
+}
+
+impl RiskLimits {
+    pub(crate) fn needs_notional(&self) -> bool {
+        self.max_order_notional.is_some() || self.max_daily_notional.is_some()
+    }
+
+    /// Whether [`Self::check_order`] and [`Self::check_position`] need the product details
+    pub(crate) fn needs_product(&self) -> bool {
+        self.needs_notional() || self.max_position_size.is_some()
+    }
+
+    pub(crate) fn check_product(&self, order: &Order) -> Result<()> {
+        match &self.allowed_products {
+            Some(allowed) if !allowed.contains(&order.product_id) => Err(DegiroError::RiskLimit(
+                RiskRule::ProductNotAllowed(order.product_id.clone()),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_order(&self, notional: Option<f64>) -> Result<()> {
+        if !self.needs_notional() {
+            return Ok(());
+        }
+        let Some(notional) = notional else {
+            return Err(DegiroError::RiskLimit(RiskRule::UnknownNotional));
+        };
+        match self.max_order_notional {
+            Some(limit) if notional > limit => {
+                Err(DegiroError::RiskLimit(RiskRule::OrderNotional {
+                    notional,
+                    limit,
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `current_size` is the position in the order's product before the order.
+    /// Amount orders are turned into a size at the last close of `product`.
+    pub(crate) fn check_position(
+        &self,
+        order: &Order,
+        product: Option<&ProductInfo>,
+        current_size: f64,
+    ) -> Result<()> {
+        let Some(limit) = self.max_position_size else {
+            return Ok(());
+        };
+
+        let order_size = match order.amount {
+            Some(amount) => product
+                .and_then(|product| {
+                    Some(amount / (product.last_price()? * product.contract_multiplier()))
+                })
+                .ok_or(DegiroError::RiskLimit(RiskRule::UnknownNotional))?,
+            None => order.size,
+        };
+        let size = match order.buy_sell {
+            OrderAction::Buy => current_size + order_size,
+            OrderAction::Sell => current_size - order_size,
+        };
+        if size.abs() > limit {
+            return Err(DegiroError::RiskLimit(RiskRule::PositionSize {
+                size,
+                limit,
+            }));
+        }
+        Ok(())
+    }
+}
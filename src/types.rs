@@ -1,37 +1,162 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
+use crate::{
+    credentials::CredentialProvider,
+    error::DegiroError,
+    risk::{RiskLimits, RiskState},
+};
 use bon::{Builder, builder};
-use jiff::civil::DateTime;
+use jiff::{
+    Timestamp, Zoned,
+    civil::{Date, DateTime, Time},
+    tz::TimeZone,
+};
 use log::LevelFilter;
-use reqwest::Client;
+use reqwest::{Client, cookie::Jar};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::{sync::oneshot, task::JoinHandle};
 
-#[derive(Builder, Debug)]
+#[derive(Builder, Debug, Clone)]
 #[builder(finish_fn = finalize)]
 pub struct DegiroClient {
+    /// Asked for the username, password and optional TOTP secret on every login
+    #[builder(with = |provider: impl CredentialProvider + 'static| Arc::new(provider) as _)]
+    pub(crate) credentials: Arc<dyn CredentialProvider>,
+    /// How long [`DegiroClient::login`] waits for the login to be approved in the
+    /// DEGIRO app. Without it logins needing confirmation fail right away.
+    pub(crate) in_app_confirmation_timeout: Option<Duration>,
+    /// What to do when DEGIRO reports the session as expired halfway through
+    #[builder(default)]
+    pub(crate) relogin_policy: ReloginPolicy,
+    /// Only log the order confirmations, modifications and cancellations that would
+    /// have been sent and return made up results. Order checks still go to DEGIRO.
+    #[builder(default)]
+    pub(crate) dry_run: bool,
+    /// Checked before orders are confirmed or modified
+    #[builder(default)]
+    pub(crate) risk_limits: RiskLimits,
     #[builder(skip)]
-    pub client: Client,
-    pub(crate) username: String,
-    pub(crate) password: String,
-    // For now just assuming everyone is reasonable
-    // and uses 2FA for their investments(!!!)
-    pub(crate) totp_secret: String,
-    pub(crate) session_id: Option<String>,
-    pub(crate) int_account: Option<u64>,
+    pub(crate) risk_state: Arc<RiskState>,
+    #[builder(skip)]
+    pub(crate) session: Arc<RwLock<Session>>,
+    #[builder(skip)]
+    pub(crate) login_lock: Arc<tokio::sync::Mutex<()>>,
     #[builder(default = LevelFilter::Off)]
     pub(crate) log_level: LevelFilter,
     // TODO: do something fancy to only allow this to be set during tests?
-    #[builder(default = "https://trader.degiro.nl".to_string())]
+    /// Stored without trailing slash, so it can be compared with the default
+    #[builder(
+        default = crate::DEFAULT_BASE_URL.to_string(),
+        with = |url: String| url.trim_end_matches('/').to_string()
+    )]
     pub(crate) base_url: String,
+    /// Kept separately from `client` so the session cookies can be exported
+    #[builder(skip)]
+    pub(crate) cookie_jar: Arc<Jar>,
+    #[builder(skip = crate::http_client_builder(&cookie_jar, log_level)
+        .build()
+        .expect("failed to build the HTTP client"))]
+    pub client: Client,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Session {
+    pub(crate) session_id: Option<String>,
+    pub(crate) int_account: Option<u64>,
+    pub(crate) logged_in_at: Option<Timestamp>,
+    pub(crate) config: Option<ClientConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReloginPolicy {
+    /// Expired sessions surface as [`crate::error::DegiroError::SessionExpired`]
+    Never,
+    /// Log in again and replay the request, except for order confirmations
+    /// where a replay could place the same order twice. Those fail with
+    /// [`crate::error::DegiroError::SessionExpired`] after the login.
+    #[default]
+    ExceptOrderConfirmations,
+    Always,
+}
+
+impl ReloginPolicy {
+    pub(crate) fn allows(self, replay: Replay) -> bool {
+        match self {
+            ReloginPolicy::Never => false,
+            ReloginPolicy::ExceptOrderConfirmations => replay == Replay::Allowed,
+            ReloginPolicy::Always => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Replay {
+    Allowed,
+    OrderConfirmation,
+}
+
+/// Handle to the task started by [`DegiroClient::keep_alive`], dropping it stops the task.
+#[derive(Debug)]
+pub struct KeepAlive {
+    pub(crate) session_lost: oneshot::Receiver<DegiroError>,
+    pub(crate) task: JoinHandle<()>,
+}
+
+impl KeepAlive {
+    /// Resolves with the error once the session is lost for good.
+    pub async fn session_lost(&mut self) -> Option<DegiroError> {
+        (&mut self.session_lost).await.ok()
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Everything needed to pick up a logged in session in another process,
+/// see [`DegiroClient::session_state`] and [`DegiroClient::resume_or_login`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionState {
+    pub session_id: String,
+    pub int_account: u64,
+    /// `name=value` pairs the cookie store holds for the base url
+    pub cookies: Vec<String>,
+    pub logged_in_at: Timestamp,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LoginRequest<'a> {
+    pub(crate) username: &'a str,
+    pub(crate) password: &'a str,
+    pub(crate) is_pass_code_reset: bool,
+    pub(crate) is_redirect_to_mobile: bool,
+    pub(crate) query_params: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InAppLoginRequest<'a> {
+    pub(crate) username: &'a str,
+    pub(crate) password: &'a str,
+    pub(crate) in_app_token: String,
+    pub(crate) query_params: serde_json::Value,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct TotpLoginRequest {
-    pub(crate) username: String,
-    pub(crate) password: String,
+pub(crate) struct TotpLoginRequest<'a> {
+    pub(crate) username: &'a str,
+    pub(crate) password: &'a str,
     pub(crate) query_params: serde_json::Value,
     pub(crate) one_time_password: String,
     pub(crate) save_device: bool,
@@ -39,30 +164,214 @@ pub(crate) struct TotpLoginRequest {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-// TODO: res can differ
-// [src/lib.rs:97:9] res.text().await? = "{\"captchaRequired\":false,\"loginFailures\":1,\"remainingAttempts\":6,\"smsCount\":0,\"status\":3,\"statusText\":\"badCredentials\"}
-pub(crate) struct TotpLoginResponse {
-    captcha_required: bool,
-    is_pass_code_enabled: bool,
-    locale: String,
-    redirect_url: String,
-    pub(crate) session_id: String,
-    pub(crate) status: i32,
-    pub(crate) status_text: String,
-    user_tokens: Vec<serde_json::Value>,
+/// Shared by all `/login/secure/login*` endpoints. Apart from `status` every field
+/// is optional, a failed login looks like:
+/// `{"captchaRequired":false,"loginFailures":1,"remainingAttempts":6,"smsCount":0,"status":3,"statusText":"badCredentials"}`
+pub(crate) struct LoginResponse {
+    #[serde(default)]
+    pub(crate) captcha_required: bool,
+    pub(crate) is_pass_code_enabled: Option<bool>,
+    pub(crate) locale: Option<String>,
+    pub(crate) redirect_url: Option<String>,
+    pub(crate) session_id: Option<String>,
+    pub(crate) status: LoginStatus,
+    pub(crate) status_text: Option<String>,
+    pub(crate) login_failures: Option<u32>,
+    pub(crate) remaining_attempts: Option<u32>,
+    pub(crate) sms_count: Option<u32>,
+    /// Only set while waiting for in-app confirmation
+    pub(crate) in_app_token: Option<String>,
+    pub(crate) user_tokens: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// From: https://github.com/Chavithra/degiro-connector (connect action)
+// 0, 3 and 6 have been seen in the wild, the others still need confirming
+pub enum LoginStatus {
+    Success,
+    BadCredentials,
+    AccountLocked,
+    TotpNeeded,
+    CaptchaRequired,
+    InAppConfirmationNeeded,
+    Unknown(i32), // fallback
+}
+
+impl From<i32> for LoginStatus {
+    fn from(v: i32) -> Self {
+        match v {
+            0 => LoginStatus::Success,
+            3 => LoginStatus::BadCredentials,
+            4 => LoginStatus::AccountLocked,
+            6 => LoginStatus::TotpNeeded,
+            7 => LoginStatus::CaptchaRequired,
+            12 => LoginStatus::InAppConfirmationNeeded,
+            other => LoginStatus::Unknown(other),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LoginStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(i32::deserialize(deserializer)?.into())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ClientConfigResponse {
+    pub(crate) data: ClientConfig,
+}
+
+/// Service URLs from `/login/secure/config`, all of them end with a slash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientConfig {
+    pub client_id: Option<u64>,
+    pub session_id: Option<String>,
+    pub trading_url: Option<String>,
+    pub pa_url: Option<String>,
+    pub product_search_url: Option<String>,
+    pub reporting_url: Option<String>,
+    pub favorites_url: Option<String>,
+    pub dictionary_url: Option<String>,
+    pub vwd_quotecast_service_url: Option<String>,
+    pub companies_service_url: Option<String>,
+    pub product_notes_url: Option<String>,
+    pub i18n_url: Option<String>,
+    pub login_url: Option<String>,
+    pub payment_service_url: Option<String>,
+    pub landing_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Service {
+    Pa,
+    Trading,
+    ProductSearch,
+    Favorites,
+    VwdQuotecast,
+    /// Not part of the client config, always derived from the base url
+    PortfolioReports,
+    /// Hosted by vwd instead of DEGIRO
+    Chart,
+}
+
+impl Service {
+    pub(crate) fn default_path(self) -> &'static str {
+        match self {
+            Service::Pa => "pa/secure",
+            Service::Trading => "trading/secure",
+            Service::ProductSearch => "product_search/secure",
+            Service::Favorites => "favorites",
+            Service::VwdQuotecast => "vwd-quotecast-service",
+            Service::PortfolioReports => "portfolio-reports/secure",
+            Service::Chart => "hchart/v1/deGiro",
+        }
+    }
+
+    /// For services living outside of the base url
+    pub(crate) fn external_url(self) -> Option<&'static str> {
+        match self {
+            Service::Chart => Some("https://charting.vwdservices.com/hchart/v1/deGiro"),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn configured_url(self, config: &ClientConfig) -> Option<&str> {
+        match self {
+            Service::Pa => config.pa_url.as_deref(),
+            Service::Trading => config.trading_url.as_deref(),
+            Service::ProductSearch => config.product_search_url.as_deref(),
+            Service::Favorites => config.favorites_url.as_deref(),
+            Service::VwdQuotecast => config.vwd_quotecast_service_url.as_deref(),
+            Service::PortfolioReports | Service::Chart => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ClientResponse {
-    pub(crate) data: ClientData,
+    pub(crate) data: ClientProfile,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct ClientData {
-    #[serde(rename = "intAccount")]
-    pub(crate) int_account: u64,
-    pub(crate) username: String,
-    pub(crate) email: String,
+/// Everything `/pa/secure/client` knows about the client behind the login.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientProfile {
+    /// The client id, not to be confused with the int account
+    pub id: Option<u64>,
+    pub int_account: u64,
+    pub logged_in_person_id: Option<u64>,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: String,
+    pub cellphone_number: Option<String>,
+    // TODO: these can be enums if we know the possible values
+    pub client_role: Option<String>,
+    pub effective_client_role: Option<String>,
+    /// The account type, e.g. "PRIVATE"
+    pub contract_type: Option<String>,
+    pub first_contact: Option<Contact>,
+    pub address: Option<Address>,
+    pub locale: Option<String>,
+    pub language: Option<String>,
+    pub culture: Option<String>,
+    pub bank_account: Option<BankAccount>,
+    /// Only set for accounts that hold their cash at flatexDEGIRO Bank
+    pub flatex_bank_account: Option<BankAccount>,
+    pub member_code: Option<String>,
+    pub trading_profile: Option<String>,
+    pub is_withdrawal_available: Option<bool>,
+    pub is_allocation_available: Option<bool>,
+    pub is_isk_client: Option<bool>,
+    pub is_collective_portfolio: Option<bool>,
+    pub is_am_client_active: Option<bool>,
+    pub can_upgrade: Option<bool>,
+    /// Anything not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub display_name: Option<String>,
+    pub nationality: Option<String>,
+    pub gender: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub place_of_birth: Option<String>,
+    pub country_of_birth: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+    pub street_address: Option<String>,
+    pub street_address_number: Option<String>,
+    pub street_address_ext: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankAccount {
+    pub bank_account_id: Option<u64>,
+    pub bic: Option<String>,
+    pub iban: Option<String>,
+    pub status: Option<String>,
+}
+
+impl ClientProfile {
+    pub fn is_flatex(&self) -> bool {
+        self.flatex_bank_account.is_some()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -150,6 +459,22 @@ pub struct ProductInfo {
     pub(crate) product_bit_types: Option<Vec<String>>,
 }
 
+impl ProductInfo {
+    /// `contract_size`, or 1 for products that don't set it
+    pub(crate) fn contract_multiplier(&self) -> f64 {
+        if self.contract_size > 0.0 {
+            self.contract_size
+        } else {
+            1.0
+        }
+    }
+
+    /// The close price, unless DEGIRO has none
+    pub(crate) fn last_price(&self) -> Option<f64> {
+        Some(self.close_price).filter(|price| *price > 0.0)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub(crate) struct ProductSearchResponse {
     pub(crate) offset: u64,
@@ -161,7 +486,210 @@ pub struct PortfolioResponse {
     pub portfolio: Portfolio,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl PortfolioResponse {
+    pub fn positions(&self) -> Vec<Position> {
+        self.portfolio.value.iter().map(Position::from).collect()
+    }
+}
+
+/// Sections to ask the update endpoint for, each with the `last_updated` of
+/// the previous response for that section or `0` to get all of it.
+/// Sections left `None` are not requested.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdateRequest {
+    pub portfolio: Option<u64>,
+    pub total_portfolio: Option<u64>,
+    pub orders: Option<u64>,
+    pub historical_orders: Option<u64>,
+    pub transactions: Option<u64>,
+    pub alerts: Option<u64>,
+    pub cash_funds: Option<u64>,
+}
+
+impl UpdateRequest {
+    /// Every section, in full
+    pub fn all() -> Self {
+        Self {
+            portfolio: Some(0),
+            total_portfolio: Some(0),
+            orders: Some(0),
+            historical_orders: Some(0),
+            transactions: Some(0),
+            alerts: Some(0),
+            cash_funds: Some(0),
+        }
+    }
+
+    /// The same sections, only asking for what changed after `response`.
+    pub fn next(&self, response: &UpdateResponse) -> Self {
+        let advance = |token: Option<u64>, last_updated: Option<u64>| {
+            token.map(|token| last_updated.unwrap_or(token))
+        };
+        Self {
+            portfolio: advance(
+                self.portfolio,
+                response.portfolio.as_ref().map(|s| s.last_updated),
+            ),
+            total_portfolio: advance(
+                self.total_portfolio,
+                response.total_portfolio.as_ref().map(|s| s.last_updated),
+            ),
+            orders: advance(
+                self.orders,
+                response.orders.as_ref().map(|s| s.last_updated),
+            ),
+            historical_orders: advance(
+                self.historical_orders,
+                response.historical_orders.as_ref().map(|s| s.last_updated),
+            ),
+            transactions: advance(
+                self.transactions,
+                response.transactions.as_ref().map(|s| s.last_updated),
+            ),
+            alerts: advance(
+                self.alerts,
+                response.alerts.as_ref().map(|s| s.last_updated),
+            ),
+            cash_funds: advance(
+                self.cash_funds,
+                response.cash_funds.as_ref().map(|s| s.last_updated),
+            ),
+        }
+    }
+
+    pub(crate) fn query(&self) -> Vec<(&'static str, String)> {
+        [
+            ("portfolio", self.portfolio),
+            ("totalPortfolio", self.total_portfolio),
+            ("orders", self.orders),
+            ("historicalOrders", self.historical_orders),
+            ("transactions", self.transactions),
+            ("alerts", self.alerts),
+            ("cashFunds", self.cash_funds),
+        ]
+        .into_iter()
+        .filter_map(|(name, token)| Some((name, token?.to_string())))
+        .collect()
+    }
+}
+
+/// Incremental updates leave out the sections that didn't change
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateResponse {
+    pub portfolio: Option<Portfolio>,
+    pub total_portfolio: Option<TotalPortfolioSection>,
+    pub orders: Option<UpdateSection<OrderRow>>,
+    pub historical_orders: Option<UpdateSection<OrderRow>>,
+    pub transactions: Option<UpdateSection<TransactionRow>>,
+    pub alerts: Option<UpdateSection<AlertRow>>,
+    pub cash_funds: Option<UpdateSection<CashFundRow>>,
+}
+
+impl UpdateResponse {
+    /// No section changed
+    pub fn is_empty(&self) -> bool {
+        self.portfolio.is_none()
+            && self.total_portfolio.is_none()
+            && self.orders.is_none()
+            && self.historical_orders.is_none()
+            && self.transactions.is_none()
+            && self.alerts.is_none()
+            && self.cash_funds.is_none()
+    }
+}
+
+/// The `totalPortfolio` section of the update endpoint, a flat list of fields
+/// that are either plain numbers or keyed by currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotalPortfolioSection {
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: u64,
+
+    pub name: String,
+
+    #[serde(default)]
+    pub value: Vec<PositionField>,
+}
+
+/// Cash and value of the whole account in its base currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotalPortfolio {
+    pub base_currency: String,
+    /// What can still be spent on new orders, `freeSpaceNew`
+    pub free_space: Option<f64>,
+    pub total_cash: Option<f64>,
+    pub report_margin: Option<f64>,
+    /// Value of all products, `reportPortfValue`
+    pub portfolio_value: Option<f64>,
+    /// Products plus cash, `reportNetliq`
+    pub net_asset_value: Option<f64>,
+    /// Not part of the section, summed from the value and `todayPlBase` of the
+    /// product positions. `None` when a position's currency can't be converted.
+    pub today_pl: Option<f64>,
+    /// Fields not mapped above, keyed by their name
+    pub extra: HashMap<String, ValueField>,
+}
+
+impl TotalPortfolio {
+    /// `fx_rates` turns the value of a position, which is in the product's
+    /// currency, into the base currency. Keyed by product id.
+    pub(crate) fn new(
+        section: &TotalPortfolioSection,
+        base_currency: String,
+        positions: &[Position],
+        fx_rates: &HashMap<String, f64>,
+    ) -> Self {
+        let mut fields: HashMap<String, ValueField> = section
+            .value
+            .iter()
+            .filter_map(|field| Some((field.name.clone(), field.value.clone()?)))
+            .collect();
+
+        let mut amount = |name: &str| match fields.remove(name)? {
+            ValueField::Object(per_currency) => per_currency.get(&base_currency).copied(),
+            other => other.as_f64(),
+        };
+        let free_space = amount("freeSpaceNew");
+        let total_cash = amount("totalCash");
+        let report_margin = amount("reportMargin");
+        let portfolio_value = amount("reportPortfValue");
+        let net_asset_value = amount("reportNetliq");
+
+        // `todayPlBase` is minus the value at the start of the day, already in base currency
+        let mut today_pl = None;
+        for position in positions
+            .iter()
+            .filter(|position| position.position_type == PositionType::Product)
+        {
+            let Some(start_of_day) = position.today_pl_base.get(&base_currency) else {
+                continue;
+            };
+            let rate = match position.value {
+                0.0 => Some(0.0),
+                _ => fx_rates.get(&position.product_id).copied(),
+            };
+            let Some(rate) = rate else {
+                today_pl = None;
+                break;
+            };
+            today_pl = Some(today_pl.unwrap_or(0.0) + position.value * rate + start_of_day);
+        }
+
+        TotalPortfolio {
+            base_currency,
+            free_space,
+            total_cash,
+            report_margin,
+            portfolio_value,
+            net_asset_value,
+            today_pl,
+            extra: fields,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Portfolio {
     #[serde(rename = "lastUpdated")]
     pub last_updated: u64,
@@ -174,39 +702,241 @@ pub struct Portfolio {
     pub is_added: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PositionRow {
-    pub name: String,
+/// A row of an update section that [`crate::portfolio::merge_rows`] can
+/// apply incremental updates of.
+pub(crate) trait UpdateRow {
+    fn id(&self) -> &str;
+    fn is_added(&self) -> bool;
+    fn is_removed(&self) -> bool;
+    fn fields_mut(&mut self) -> &mut Vec<PositionField>;
+}
 
-    /// A String because currencies have their value as ID
-    /// e.g. "USD"
-    pub id: String,
+/// Every section is made of rows with a list of name/value fields, each
+/// section with fields of its own.
+macro_rules! update_row {
+    ($(#[$doc:meta])* $row:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct $row {
+            pub name: String,
 
-    pub value: Vec<PositionField>,
+            /// A String because cash rows use the currency as id, e.g. "USD"
+            pub id: String,
 
-    #[serde(rename = "isAdded")]
-    pub is_added: bool,
+            /// In incremental updates only the fields that changed
+            #[serde(default)]
+            pub value: Vec<PositionField>,
+
+            /// Set for rows that are new since the requested `lastUpdated`,
+            /// which then carry all of their fields
+            #[serde(rename = "isAdded", default)]
+            pub is_added: bool,
+
+            /// Set for rows that disappeared since the requested `lastUpdated`
+            #[serde(
+                rename = "isRemoved",
+                default,
+                skip_serializing_if = "std::ops::Not::not"
+            )]
+            pub is_removed: bool,
+        }
+
+        impl $row {
+            pub fn field(&self, name: &str) -> Option<&ValueField> {
+                self.value
+                    .iter()
+                    .find(|field| field.name == name)
+                    .and_then(|field| field.value.as_ref())
+            }
+        }
+
+        impl UpdateRow for $row {
+            fn id(&self) -> &str {
+                &self.id
+            }
+
+            fn is_added(&self) -> bool {
+                self.is_added
+            }
+
+            fn is_removed(&self) -> bool {
+                self.is_removed
+            }
+
+            fn fields_mut(&mut self) -> &mut Vec<PositionField> {
+                &mut self.value
+            }
+        }
+    };
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+update_row! {
+    /// A row of the `portfolio` section, read with [`Position::from`]
+    PositionRow
+}
+
+update_row! {
+    /// A row of the `orders` and `historicalOrders` sections, read with
+    /// [`OpenOrder::try_from`]. The id is the order id.
+    OrderRow
+}
+
+update_row! {
+    /// A row of the `transactions` section
+    TransactionRow
+}
+
+update_row! {
+    /// A row of the `alerts` section
+    AlertRow
+}
+
+update_row! {
+    /// A row of the `cashFunds` section, one per currency
+    CashFundRow
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionField {
     pub name: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<ValueField>,
 
-    #[serde(rename = "isAdded")]
+    #[serde(rename = "isAdded", default)]
     pub is_added: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ValueField {
     String(String),
     Number(f64),
+    Bool(bool),
     Object(HashMap<String, f64>),
 }
 
+impl ValueField {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            ValueField::Number(n) => Some(*n),
+            ValueField::String(s) => s.parse().ok(),
+            ValueField::Bool(_) | ValueField::Object(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionType {
+    Product,
+    Cash,
+    Unknown, // fallback
+}
+
+impl From<&str> for PositionType {
+    fn from(v: &str) -> Self {
+        match v {
+            "PRODUCT" => PositionType::Product,
+            "CASH" => PositionType::Cash,
+            _ => PositionType::Unknown,
+        }
+    }
+}
+
+/// A [`PositionRow`] with its name/value fields looked up. Amounts are in the
+/// product's currency, the `*_pl_base` maps are keyed by currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    /// Same as [`PositionRow::id`], so a currency for cash positions
+    pub product_id: String,
+    pub position_type: PositionType,
+    pub size: f64,
+    pub price: f64,
+    pub value: f64,
+    pub break_even_price: Option<f64>,
+    pub average_fx_rate: Option<f64>,
+    pub realized_product_pl: Option<f64>,
+    pub realized_fx_pl: Option<f64>,
+    pub today_realized_product_pl: Option<f64>,
+    pub today_realized_fx_pl: Option<f64>,
+    pub pl_base: HashMap<String, f64>,
+    pub today_pl_base: HashMap<String, f64>,
+    pub accrued_interest: Option<f64>,
+    /// Fields not mapped above, keyed by their name
+    pub extra: HashMap<String, ValueField>,
+}
+
+impl Position {
+    /// Paper profit of the open position, `None` without a break-even price.
+    /// Scaled from `value` so the contract size of options and futures is
+    /// taken into account.
+    pub fn unrealized_pl(&self) -> Option<f64> {
+        let break_even = self.break_even_price?;
+        if self.price == 0.0 {
+            return None;
+        }
+        Some(self.value - break_even * self.value / self.price)
+    }
+}
+
+/// The fields of a row by name, leaving out the ones without a value.
+fn row_fields(fields: &[PositionField]) -> HashMap<String, ValueField> {
+    fields
+        .iter()
+        .filter_map(|field| Some((field.name.clone(), field.value.clone()?)))
+        .collect()
+}
+
+impl From<&PositionRow> for Position {
+    fn from(row: &PositionRow) -> Self {
+        let mut fields = row_fields(&row.value);
+
+        let mut number = |name: &str| fields.remove(name).as_ref().and_then(ValueField::as_f64);
+        let size = number("size").unwrap_or_default();
+        let price = number("price").unwrap_or_default();
+        let value = number("value").unwrap_or_default();
+        let break_even_price = number("breakEvenPrice");
+        let average_fx_rate = number("averageFxRate");
+        let realized_product_pl = number("realizedProductPl");
+        let realized_fx_pl = number("realizedFxPl");
+        let today_realized_product_pl = number("todayRealizedProductPl");
+        let today_realized_fx_pl = number("todayRealizedFxPl");
+        let accrued_interest = number("accruedInterest");
+
+        let mut per_currency = |name: &str| match fields.remove(name) {
+            Some(ValueField::Object(map)) => map,
+            _ => HashMap::new(),
+        };
+        let pl_base = per_currency("plBase");
+        let today_pl_base = per_currency("todayPlBase");
+
+        let position_type = match fields.remove("positionType") {
+            Some(ValueField::String(v)) => PositionType::from(v.as_str()),
+            _ => PositionType::Unknown,
+        };
+        // duplicate of the row id
+        fields.remove("id");
+
+        Position {
+            product_id: row.id.clone(),
+            position_type,
+            size,
+            price,
+            value,
+            break_even_price,
+            average_fx_rate,
+            realized_product_pl,
+            realized_fx_pl,
+            today_realized_product_pl,
+            today_realized_fx_pl,
+            pl_base,
+            today_pl_base,
+            accrued_interest,
+            extra: fields,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct HistoryResponse {
     pub data: Vec<HistoryItem>,
@@ -220,7 +950,7 @@ pub enum BuySell {
     S,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
 // From: https://github.com/Chavithra/degiro-connector/blob/bffe906194a6f3e91fafdfb8830efa894e8751a8/degiro_connector/trading/models/order.py#L30-L34
 // not sure what these unknown types are...
@@ -246,26 +976,48 @@ impl<'de> Deserialize<'de> for OrderTimeType {
     where
         D: Deserializer<'de>,
     {
-        let v = i32::deserialize(deserializer)?;
-        Ok(match v {
+        Ok(i32::deserialize(deserializer)?.into())
+    }
+}
+
+impl OrderTimeType {
+    /// Name used in [`ProductInfo`]'s `orderTimeTypes`
+    pub(crate) fn product_info_name(self) -> Option<&'static str> {
+        match self {
+            OrderTimeType::GoodTillDay => Some("DAY"),
+            OrderTimeType::GoodTillCanceled => Some("GTC"),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for OrderTimeType {
+    fn from(v: i32) -> Self {
+        match v {
             0 => OrderTimeType::Unknown0,
             1 => OrderTimeType::GoodTillDay,
             2 => OrderTimeType::Unknown2,
             3 => OrderTimeType::GoodTillCanceled,
             _ => OrderTimeType::Unknown,
-        })
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
 // From: https://github.com/Chavithra/degiro-connector/blob/bffe906194a6f3e91fafdfb8830efa894e8751a8/degiro_connector/trading/models/order.py#L14-L27
-// TODO: Find out remaining types & whether they are relevant
 pub enum OrderType {
     Limit = 0,
     StopLimit = 1,
     Market = 2,
     StopLoss = 3,
+    /// Only read from open orders: [`Order`] has no field for the trail yet,
+    /// since its wire format is unconfirmed
+    TrailingStop = 13,
+    /// Investment funds, bought or sold for an amount of money
+    StandardAmount = 14,
+    /// Investment funds, bought or sold for a number of units
+    StandardSize = 15,
     Unknown = -1, // fallback
 }
 
@@ -283,35 +1035,355 @@ impl<'de> Deserialize<'de> for OrderType {
     where
         D: Deserializer<'de>,
     {
-        let v = i32::deserialize(deserializer)?;
-        Ok(match v {
+        Ok(i32::deserialize(deserializer)?.into())
+    }
+}
+
+impl OrderType {
+    /// Name used in [`ProductInfo`]'s `buyOrderTypes` and `sellOrderTypes`
+    pub(crate) fn product_info_name(self) -> Option<&'static str> {
+        match self {
+            OrderType::Limit => Some("LIMIT"),
+            OrderType::StopLimit => Some("STOPLIMIT"),
+            OrderType::Market => Some("MARKET"),
+            OrderType::StopLoss => Some("STOPLOSS"),
+            OrderType::TrailingStop => Some("TRAILINGSTOP"),
+            OrderType::StandardAmount => Some("STANDARDAMOUNT"),
+            OrderType::StandardSize => Some("STANDARDSIZE"),
+            OrderType::Unknown => None,
+        }
+    }
+}
+
+impl From<i32> for OrderType {
+    fn from(v: i32) -> Self {
+        match v {
             0 => OrderType::Limit,
             1 => OrderType::StopLimit,
             2 => OrderType::Market,
             3 => OrderType::StopLoss,
+            13 => OrderType::TrailingStop,
+            14 => OrderType::StandardAmount,
+            15 => OrderType::StandardSize,
             _ => OrderType::Unknown,
-        })
+        }
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum OrderAction {
     Buy,
     Sell,
 }
 
-#[derive(Serialize, Debug)]
+/// A section of the update endpoint, with a row type of its own for each
+/// section like [`Portfolio`] has [`PositionRow`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSection<R> {
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: u64,
+
+    pub name: String,
+
+    #[serde(default = "Vec::new")]
+    pub value: Vec<R>,
+}
+
+impl UpdateSection<OrderRow> {
+    /// For the `orders` section, rows that can't be read as an order are logged and skipped.
+    pub fn open_orders(&self) -> Vec<OpenOrder> {
+        self.value
+            .iter()
+            .filter(|row| !row.is_removed)
+            .filter_map(|row| match OpenOrder::try_from(row) {
+                Ok(order) => Some(order),
+                Err(e) => {
+                    log::warn!("skipping order row {}: {e}", row.id);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl UpdateSection<TransactionRow> {
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.value
+            .iter()
+            .filter(|row| !row.is_removed)
+            .map(Transaction::from)
+            .collect()
+    }
+}
+
+impl UpdateSection<CashFundRow> {
+    pub fn cash_funds(&self) -> Vec<CashFundBalance> {
+        self.value
+            .iter()
+            .filter(|row| !row.is_removed)
+            .map(CashFundBalance::from)
+            .collect()
+    }
+}
+
+/// A [`TransactionRow`] with its fields looked up, named like the
+/// [`HistoryItem`] of the transactions history. Amounts are in the product's
+/// currency unless they say otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub id: String,
+    pub product_id: Option<String>,
+    pub action: Option<OrderAction>,
+    /// In DEGIRO's time zone, read like [`OpenOrder::created`]
+    pub date: Option<DateTime>,
+    pub price: Option<f64>,
+    pub quantity: Option<f64>,
+    pub total: Option<f64>,
+    pub total_in_base_currency: Option<f64>,
+    pub fee_in_base_currency: Option<f64>,
+    /// Fields not mapped above, keyed by their name
+    pub extra: HashMap<String, ValueField>,
+}
+
+impl From<&TransactionRow> for Transaction {
+    fn from(row: &TransactionRow) -> Self {
+        let mut fields = row_fields(&row.value);
+
+        let mut number = |name: &str| fields.remove(name).as_ref().and_then(ValueField::as_f64);
+        let price = number("price");
+        let quantity = number("quantity");
+        let total = number("total");
+        let total_in_base_currency = number("totalInBaseCurrency");
+        let fee_in_base_currency = number("feeInBaseCurrency");
+
+        let mut string = |name: &str| match fields.remove(name) {
+            Some(ValueField::String(s)) => Some(s),
+            Some(ValueField::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+        let product_id = string("productId");
+        let action = match string("buysell").as_deref() {
+            Some("B") | Some("BUY") => Some(OrderAction::Buy),
+            Some("S") | Some("SELL") => Some(OrderAction::Sell),
+            _ => None,
+        };
+        let date = string("date").and_then(|date| parse_order_date(&date, degiro_today()));
+        // duplicate of the row id
+        fields.remove("id");
+
+        Transaction {
+            id: row.id.clone(),
+            product_id,
+            action,
+            date,
+            price,
+            quantity,
+            total,
+            total_in_base_currency,
+            fee_in_base_currency,
+            extra: fields,
+        }
+    }
+}
+
+/// A [`CashFundRow`] with its fields looked up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashFundBalance {
+    pub id: String,
+    pub currency: Option<String>,
+    pub value: Option<f64>,
+    /// Fields not mapped above, keyed by their name
+    pub extra: HashMap<String, ValueField>,
+}
+
+impl From<&CashFundRow> for CashFundBalance {
+    fn from(row: &CashFundRow) -> Self {
+        let mut fields = row_fields(&row.value);
+
+        let value = fields.remove("value").as_ref().and_then(ValueField::as_f64);
+        let currency = match fields.remove("currencyCode") {
+            Some(ValueField::String(currency)) => Some(currency),
+            _ => None,
+        };
+        // duplicate of the row id
+        fields.remove("id");
+
+        CashFundBalance {
+            id: row.id.clone(),
+            currency,
+            value,
+            extra: fields,
+        }
+    }
+}
+
+/// An order that is still pending at DEGIRO.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenOrder {
+    pub order_id: String,
+    pub product_id: String,
+    pub action: OrderAction,
+    pub order_type: OrderType,
+    pub time_type: OrderTimeType,
+    pub size: f64,
+    pub price: f64,
+    pub stop_price: Option<f64>,
+    /// The row's `quantity`. Taken to be the part already filled, but that
+    /// is unconfirmed: it has only been seen matching `size` so far.
+    pub quantity_filled: Option<f64>,
+    /// In DEGIRO's time zone, midnight for orders from before today since
+    /// those only come with a date
+    pub created: Option<DateTime>,
+    pub is_modifiable: bool,
+    pub is_deletable: bool,
+}
+
+impl TryFrom<&OrderRow> for OpenOrder {
+    type Error = DegiroError;
+
+    fn try_from(row: &OrderRow) -> Result<Self, Self::Error> {
+        let missing = |name: &str| DegiroError::UnexpectedResponse(format!("order without {name}"));
+        let number = |name: &str| row.field(name).and_then(ValueField::as_f64);
+        let string = |name: &str| match row.field(name) {
+            Some(ValueField::String(s)) => Some(s.clone()),
+            Some(ValueField::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+        let flag = |name: &str| matches!(row.field(name), Some(ValueField::Bool(true)));
+
+        let action = match string("buysell").as_deref() {
+            Some("B") | Some("BUY") => OrderAction::Buy,
+            Some("S") | Some("SELL") => OrderAction::Sell,
+            _ => return Err(missing("buysell")),
+        };
+
+        Ok(OpenOrder {
+            order_id: row.id.clone(),
+            product_id: string("productId").ok_or_else(|| missing("productId"))?,
+            action,
+            order_type: number("orderTypeId").map_or(OrderType::Unknown, |v| (v as i32).into()),
+            time_type: number("orderTimeTypeId")
+                .map_or(OrderTimeType::Unknown, |v| (v as i32).into()),
+            size: number("size").ok_or_else(|| missing("size"))?,
+            price: number("price").unwrap_or_default(),
+            stop_price: number("stopPrice").filter(|p| *p != 0.0),
+            quantity_filled: number("quantity"),
+            created: string("date").and_then(|date| parse_order_date(&date, degiro_today())),
+            is_modifiable: flag("isModifiable"),
+            is_deletable: flag("isDeletable"),
+        })
+    }
+}
+
+/// The orders section sends `HH:MM` for orders placed today and `DD/MM/YYYY`
+/// for older ones.
+fn parse_order_date(value: &str, today: Date) -> Option<DateTime> {
+    // A full datetime parses as a time as well, so it goes first
+    if let Ok(datetime) = value.parse::<DateTime>() {
+        return Some(datetime);
+    }
+    if let Ok(time) = value.parse::<Time>() {
+        return Some(today.to_datetime(time));
+    }
+    match Date::strptime("%d/%m/%Y", value) {
+        Ok(date) => Some(date.to_datetime(Time::midnight())),
+        Err(_) => {
+            log::debug!("unknown order date format {value:?}");
+            None
+        }
+    }
+}
+
+/// Today's date in Amsterdam, where DEGIRO's times are from.
+fn degiro_today() -> Date {
+    match TimeZone::get("Europe/Amsterdam") {
+        Ok(tz) => Timestamp::now().to_zoned(tz).date(),
+        Err(_) => Zoned::now().date(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Order {
     pub buy_sell: OrderAction,
     pub order_type: OrderType,
     pub product_id: String,
+    /// In contracts, left at 0 for [`OrderType::StandardAmount`] orders
     pub size: f64,
-    pub price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
     pub time_type: OrderTimeType,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub stop_price: Option<f64>,
+    /// Only for [`OrderType::StandardAmount`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<f64>,
+}
+
+impl Order {
+    /// Value of the order in the product's currency: the amount of
+    /// [`OrderType::StandardAmount`] orders, otherwise the size in contracts of
+    /// `contract_size` times the limit or stop price. Orders without one, like
+    /// market orders, are valued at the last close. `None` without any price.
+    pub fn notional(&self, product: &ProductInfo) -> Option<f64> {
+        if let Some(amount) = self.amount {
+            return Some(amount);
+        }
+        let price = self.price.or(self.stop_price).or(product.last_price())?;
+        Some(price * self.size * product.contract_multiplier())
+    }
+}
+
+/// Fails with [`DegiroError::OrderNotModifiable`] for order types whose amount
+/// or trailing stop the orders section doesn't tell, since sending them without
+/// would change the order.
+impl TryFrom<&OpenOrder> for Order {
+    type Error = DegiroError;
+
+    fn try_from(order: &OpenOrder) -> Result<Self, Self::Error> {
+        match order.order_type {
+            OrderType::TrailingStop | OrderType::StandardAmount | OrderType::Unknown => {
+                Err(DegiroError::OrderNotModifiable(order.order_id.clone()))
+            }
+            _ => Ok(Order {
+                buy_sell: order.action,
+                order_type: order.order_type,
+                product_id: order.product_id.clone(),
+                size: order.size,
+                price: Some(order.price).filter(|p| *p != 0.0),
+                time_type: order.time_type,
+                stop_price: order.stop_price,
+                amount: None,
+            }),
+        }
+    }
+}
+
+/// Changes for [`crate::types::DegiroClient::modify_order`], fields left `None` are kept.
+#[derive(Debug, Clone, Default)]
+pub struct OrderChanges {
+    pub size: Option<f64>,
+    pub price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub time_type: Option<OrderTimeType>,
+}
+
+impl OrderChanges {
+    pub(crate) fn apply(&self, order: &mut Order) {
+        if let Some(size) = self.size {
+            order.size = size;
+        }
+        if let Some(price) = self.price {
+            order.price = Some(price);
+        }
+        if let Some(stop_price) = self.stop_price {
+            order.stop_price = Some(stop_price);
+        }
+        if let Some(time_type) = self.time_type {
+            order.time_type = time_type;
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -344,9 +1416,49 @@ pub struct OrderConfirmationResponse {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderConfirmation {
-    order_id: String,
-    response_datetime: Option<DateTime>,
-    request_duration: Option<Duration>,
+    pub order_id: String,
+    pub response_datetime: Option<DateTime>,
+    pub request_duration: Option<Duration>,
+}
+
+/// Outcome of [`crate::types::DegiroClient::place_order`], the check the order was
+/// approved on and the id DEGIRO gave the order.
+#[derive(Debug)]
+pub struct PlacedOrder {
+    pub order_id: String,
+    pub check: OrderCheck,
+    pub confirmation: OrderConfirmation,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct OrderUpdateResponse {
+    pub(crate) data: Option<OrderUpdateData>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OrderUpdateData {
+    pub(crate) response_datetime: Option<DateTime>,
+}
+
+/// Result of a successful [`crate::types::DegiroClient::modify_order`] or
+/// [`crate::types::DegiroClient::cancel_order`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderUpdate {
+    pub order_id: String,
+    // TODO: DEGIRO sometimes answers with an empty body, so this is not always there
+    pub response_datetime: Option<DateTime>,
+}
+
+/// Body of a rejected order request, e.g. `{"errors":[{"text":"..."}]}`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct OrderErrorResponse {
+    pub(crate) errors: Vec<OrderErrorMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OrderErrorMessage {
+    pub(crate) text: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -413,6 +1525,26 @@ pub struct CurrencyPair {
     pub price: String,
 }
 
+impl AccountInfo {
+    /// What one unit of `currency` is currently worth in the base currency,
+    /// from `currency_pairs` like `EURUSD` where the price is in the second one.
+    pub fn fx_rate(&self, currency: &str) -> Option<f64> {
+        if currency == self.base_currency {
+            return Some(1.0);
+        }
+        let price = |pair: String| {
+            self.currency_pairs
+                .get(&pair)?
+                .price
+                .parse::<f64>()
+                .ok()
+                .filter(|price| *price > 0.0)
+        };
+        price(format!("{currency}{}", self.base_currency))
+            .or_else(|| Some(1.0 / price(format!("{}{currency}", self.base_currency))?))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AccountOverviewResponse {
     pub data: AccountOverview,
@@ -0,0 +1,337 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::Duration,
+};
+
+use futures::{Stream, stream};
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::{
+    error::DegiroError,
+    portfolio::merge_rows,
+    types::{
+        DegiroClient, OpenOrder, OrderAction, OrderRow, Position, PositionRow, PositionType,
+        TotalPortfolio, TotalPortfolioSection, Transaction, UpdateRequest, UpdateResponse,
+    },
+};
+
+type Result<T> = std::result::Result<T, DegiroError>;
+
+/// What [`DegiroClient::watch`] polls for and how often.
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Time between two polls. Polls that are due while the previous events
+    /// haven't been consumed yet are delayed, never bunched up.
+    pub interval: Duration,
+    pub positions: bool,
+    pub orders: bool,
+    pub cash: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            positions: true,
+            orders: true,
+            cash: true,
+        }
+    }
+}
+
+/// A change seen by [`DegiroClient::watch`]. The first poll reports the
+/// positions and orders that are already there as opened and created.
+///
+/// The orders section doesn't tell fills apart from cancellations, so fills
+/// come from the new rows of the transactions section, matched to the orders
+/// by product and direction. A removed order without fills is only reported
+/// as cancelled one poll later, in case its transaction arrives late.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    PositionOpened(Position),
+    PositionChanged(Position),
+    /// The position as it was before it was closed
+    PositionClosed(Position),
+    OrderCreated(OpenOrder),
+    /// Changed without being filled, e.g. a new limit price
+    OrderChanged(OpenOrder),
+    /// Some of the order was filled, `filled` is the total so far
+    OrderPartiallyFilled {
+        order: OpenOrder,
+        filled: f64,
+    },
+    /// The order as it was before it was filled
+    OrderFilled(OpenOrder),
+    /// The order as it was before it was cancelled or expired, possibly
+    /// after being filled in part
+    OrderCancelled(OpenOrder),
+    CashChanged(TotalPortfolio),
+}
+
+/// What is left of a new transaction to match to the orders.
+struct Fill {
+    product_id: String,
+    action: OrderAction,
+    quantity: f64,
+}
+
+impl Fill {
+    fn new(transaction: Transaction) -> Option<Self> {
+        Some(Fill {
+            product_id: transaction.product_id?,
+            action: transaction.action?,
+            // Sells may come as negative quantities
+            quantity: transaction.quantity?.abs(),
+        })
+    }
+}
+
+pub(crate) struct Watcher {
+    client: DegiroClient,
+    options: WatchOptions,
+    interval: Option<Interval>,
+    request: UpdateRequest,
+    positions: BTreeMap<String, PositionRow>,
+    orders: BTreeMap<String, OrderRow>,
+    /// How much of each order the transactions filled so far
+    filled: HashMap<String, f64>,
+    /// Orders removed in the previous poll without being completely filled
+    removed: Vec<OpenOrder>,
+    total: Option<TotalPortfolioSection>,
+    /// Set until a [`WatchEvent::CashChanged`] for the latest `total` was built,
+    /// so a failure to build it is retried on the next poll
+    cash_changed: bool,
+    /// Currency of each product id, for converting today's P&L
+    currencies: HashMap<String, String>,
+    pending: VecDeque<WatchEvent>,
+}
+
+impl Watcher {
+    pub(crate) fn new(client: DegiroClient, options: WatchOptions) -> Self {
+        let initial = |enabled: bool| enabled.then_some(0);
+        let request = UpdateRequest {
+            portfolio: initial(options.positions || options.cash),
+            total_portfolio: initial(options.cash),
+            orders: initial(options.orders),
+            transactions: initial(options.orders),
+            ..Default::default()
+        };
+
+        Self {
+            client,
+            options,
+            interval: None,
+            request,
+            positions: BTreeMap::new(),
+            orders: BTreeMap::new(),
+            filled: HashMap::new(),
+            removed: Vec::new(),
+            total: None,
+            cash_changed: false,
+            currencies: HashMap::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Events are only produced while the stream is being read, so a slow
+    /// consumer slows down the polling instead of building up a backlog.
+    pub(crate) fn into_stream(self) -> impl Stream<Item = Result<WatchEvent>> + Send {
+        stream::unfold(self, |mut watcher| async move {
+            loop {
+                if let Some(event) = watcher.pending.pop_front() {
+                    return Some((Ok(event), watcher));
+                }
+
+                let period = watcher.options.interval;
+                watcher
+                    .interval
+                    .get_or_insert_with(|| {
+                        let mut interval = tokio::time::interval(period);
+                        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                        interval
+                    })
+                    .tick()
+                    .await;
+
+                if let Err(e) = watcher.poll().await {
+                    return Some((Err(e), watcher));
+                }
+            }
+        })
+    }
+
+    async fn poll(&mut self) -> Result<()> {
+        // The transactions of the first poll are from before the watch
+        let first = self.request.transactions == Some(0);
+        let response = self.client.poll_update(&self.request).await?;
+        self.request = self.request.next(&response);
+        self.apply(response, first);
+
+        if self.cash_changed
+            && let Some(total) = &self.total
+        {
+            let positions: Vec<Position> = self.positions.values().map(Position::from).collect();
+            let total = self
+                .client
+                .total_portfolio(total, &positions, &mut self.currencies)
+                .await?;
+            self.cash_changed = false;
+            self.pending.push_back(WatchEvent::CashChanged(total));
+        }
+        Ok(())
+    }
+
+    /// Queues the position and order events, and merges the total portfolio
+    /// section for [`Self::poll`] to report.
+    fn apply(&mut self, response: UpdateResponse, first: bool) {
+        if let Some(portfolio) = response.portfolio {
+            let before: BTreeMap<String, Position> = self
+                .positions
+                .iter()
+                .map(|(id, row)| (id.clone(), Position::from(row)))
+                .collect();
+            let changes = merge_rows(&mut self.positions, portfolio.value);
+
+            if self.options.positions {
+                let touched = changes.added.iter().chain(&changes.changed);
+                for id in touched {
+                    let position = Position::from(&self.positions[id]);
+                    let previous = before.get(id).filter(|p| p.size != 0.0);
+                    let event = match previous {
+                        _ if position.position_type != PositionType::Product => continue,
+                        None if position.size == 0.0 => continue,
+                        None => WatchEvent::PositionOpened(position),
+                        Some(previous) if position.size == 0.0 => {
+                            WatchEvent::PositionClosed(previous.clone())
+                        }
+                        Some(previous) if *previous == position => continue,
+                        Some(_) => WatchEvent::PositionChanged(position),
+                    };
+                    self.pending.push_back(event);
+                }
+
+                for row in changes.removed {
+                    let position = Position::from(&row);
+                    if position.position_type == PositionType::Product && position.size != 0.0 {
+                        self.pending.push_back(WatchEvent::PositionClosed(position));
+                    }
+                }
+            }
+        }
+
+        let mut fills: Vec<Fill> = match response.transactions {
+            Some(transactions) if !first => transactions
+                .value
+                .iter()
+                .filter(|row| row.is_added)
+                .filter_map(|row| Fill::new(Transaction::from(row)))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let changes = match response.orders {
+            Some(orders) => merge_rows(&mut self.orders, orders.value),
+            None => Default::default(),
+        };
+        let order = |row: &OrderRow| OpenOrder::try_from(row).ok();
+
+        // Fills go to the orders that are gone first, they are the most likely to be done
+        let held = std::mem::take(&mut self.removed);
+        let removed = changes.removed.iter().filter_map(order);
+        for (order, seen_before) in held
+            .into_iter()
+            .map(|order| (order, true))
+            .chain(removed.map(|order| (order, false)))
+        {
+            let (taken, filled) = self.take_fills(&mut fills, &order);
+            if filled > 0.0 && filled >= order.size {
+                self.filled.remove(&order.order_id);
+                self.pending.push_back(WatchEvent::OrderFilled(order));
+                continue;
+            }
+            if taken > 0.0 {
+                self.pending.push_back(WatchEvent::OrderPartiallyFilled {
+                    order: order.clone(),
+                    filled,
+                });
+            }
+            if seen_before {
+                self.filled.remove(&order.order_id);
+                self.pending.push_back(WatchEvent::OrderCancelled(order));
+            } else {
+                self.removed.push(order);
+            }
+        }
+
+        for id in &changes.added {
+            if let Some(order) = order(&self.orders[id]) {
+                self.pending.push_back(WatchEvent::OrderCreated(order));
+            }
+        }
+        let open: Vec<OpenOrder> = self.orders.values().filter_map(order).collect();
+        for order in open {
+            let (taken, filled) = self.take_fills(&mut fills, &order);
+            if taken > 0.0 {
+                self.pending
+                    .push_back(WatchEvent::OrderPartiallyFilled { order, filled });
+            } else if changes.changed.contains(&order.order_id) {
+                self.pending.push_back(WatchEvent::OrderChanged(order));
+            }
+        }
+
+        let Some(section) = response.total_portfolio else {
+            return;
+        };
+        match &mut self.total {
+            None => {
+                self.total = Some(section);
+                self.cash_changed = true;
+            }
+            Some(total) => {
+                total.last_updated = section.last_updated;
+                for field in section.value {
+                    match total.value.iter_mut().find(|f| f.name == field.name) {
+                        Some(existing) if existing.value == field.value => {}
+                        Some(existing) => {
+                            *existing = field;
+                            self.cash_changed = true;
+                        }
+                        None => {
+                            total.value.push(field);
+                            self.cash_changed = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Takes what is left of `order` from the `fills` in its product and
+    /// direction, returning the quantity taken and the total filled so far.
+    fn take_fills(&mut self, fills: &mut Vec<Fill>, order: &OpenOrder) -> (f64, f64) {
+        let before = self
+            .filled
+            .get(&order.order_id)
+            .copied()
+            .unwrap_or_default();
+        let mut filled = before;
+        for fill in fills
+            .iter_mut()
+            .filter(|fill| fill.product_id == order.product_id && fill.action == order.action)
+        {
+            // Amount orders have no size to fill up, they take whatever matches
+            let quantity = match order.size {
+                size if size > 0.0 => fill.quantity.min(size - filled),
+                _ => fill.quantity,
+            };
+            fill.quantity -= quantity;
+            filled += quantity;
+        }
+        fills.retain(|fill| fill.quantity > 0.0);
+
+        if filled > before {
+            self.filled.insert(order.order_id.clone(), filled);
+        }
+        (filled - before, filled)
+    }
+}
@@ -1,9 +1,23 @@
-use degiro_rs::types::DegiroClient;
+use degiro_rs::{
+    batch::{BatchOptions, OrderOutcome},
+    chart::{ChartPeriod, ChartResolution},
+    credentials::{CommandCredentials, Credentials},
+    error::DegiroError,
+    risk::{RiskLimits, RiskRule},
+    types::{
+        DegiroClient, Order, OrderAction, OrderChanges, OrderTimeType, OrderType,
+        PortfolioResponse, Position, PositionType, ProductInfo, ReloginPolicy, SessionState,
+        UpdateRequest, ValueField,
+    },
+    watch::{WatchEvent, WatchOptions},
+};
+use futures::StreamExt;
 use httpmock::{
-    Method::{GET, POST},
-    MockServer,
+    Method::{DELETE, GET, POST, PUT},
+    Mock, MockServer,
 };
 use serde_json::json;
+use std::time::Duration;
 
 const DEGIRO_USERNAME: &str = "TEST";
 const DEGIRO_PASSWORD: &str = "TEST";
@@ -17,21 +31,107 @@ struct TestSetup {
 impl TestSetup {
     async fn new() -> Self {
         let server = MockServer::start_async().await;
+        let client = test_client(&server);
 
-        let client = DegiroClient::builder()
-            .username(DEGIRO_USERNAME.to_string())
-            .password(DEGIRO_PASSWORD.to_string())
-            .totp_secret(DEGIRO_TOTP_SECRET.to_string())
-            .base_url(server.base_url())
-            .finalize();
+        Self { server, client }
+    }
+
+    async fn logged_in() -> Self {
+        let server = MockServer::start_async().await;
+        let client = logged_in_client(&server);
 
         Self { server, client }
     }
 }
 
+fn test_client(server: &MockServer) -> DegiroClient {
+    DegiroClient::builder()
+        .credentials(
+            Credentials::new(DEGIRO_USERNAME, DEGIRO_PASSWORD).with_totp_secret(DEGIRO_TOTP_SECRET),
+        )
+        .base_url(server.base_url())
+        .finalize()
+}
+
+/// A client that already has the session `mock-session-123` of account 12345678
+fn logged_in_client(server: &MockServer) -> DegiroClient {
+    let client = test_client(server);
+    restore_mock_session(&client);
+    client
+}
+
+fn restore_mock_session(client: &DegiroClient) {
+    client
+        .restore_session(SessionState {
+            session_id: "mock-session-123".to_string(),
+            int_account: 12345678,
+            cookies: vec![],
+            logged_in_at: "2025-06-01T08:00:00Z".parse().unwrap(),
+        })
+        .unwrap();
+}
+
+async fn mock_client_config(server: &MockServer) -> Mock<'_> {
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/login/secure/config");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "clientId": 1234567,
+                        "sessionId": "mock-session-123",
+                        "tradingUrl": "https://trader.degiro.nl/trading/secure/",
+                        "paUrl": "https://trader.degiro.nl/pa/secure/",
+                        "productSearchUrl": "https://trader.degiro.nl/product_search/secure/",
+                        "reportingUrl": "https://trader.degiro.nl/reporting/secure/",
+                        "favoritesUrl": "https://trader.degiro.nl/favorites/",
+                        "dictionaryUrl": "https://trader.degiro.nl/product_search/config/dictionary/",
+                        "vwdQuotecastServiceUrl": "https://trader.degiro.nl/vwd-quotecast-service/"
+                    }
+                }));
+        })
+        .await
+}
+
+/// Serves the product details of `products`, an object keyed by product id,
+/// whichever ids are asked for.
+async fn mock_products(server: &MockServer, products: serde_json::Value) -> Mock<'_> {
+    server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/product_search/secure/v5/products/info");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "data": products }));
+        })
+        .await
+}
+
+/// Account info of an EUR account with the given `currencyPairs`
+async fn mock_account_info(server: &MockServer, currency_pairs: serde_json::Value) -> Mock<'_> {
+    server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/account/info/12345678;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "baseCurrency": "EUR",
+                        "cashFunds": {},
+                        "clientId": 1234567,
+                        "currencyPairs": currency_pairs,
+                        "marginType": "NONE"
+                    }
+                }));
+        })
+        .await
+}
+
 #[tokio::test]
 async fn login_success() {
-    let mut setup = TestSetup::new().await;
+    let setup = TestSetup::new().await;
 
     let totp_mock = setup
         .server
@@ -52,6 +152,8 @@ async fn login_success() {
         })
         .await;
 
+    let config_mock = mock_client_config(&setup.server).await;
+
     let client_mock = setup
         .server
         .mock_async(|when, then| {
@@ -74,4 +176,2378 @@ async fn login_success() {
 
     totp_mock.assert();
     client_mock.assert();
+    config_mock.assert();
+}
+
+#[tokio::test]
+async fn login_bad_credentials() {
+    let setup = TestSetup::new().await;
+
+    let totp_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login/totp");
+            then.status(400)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "loginFailures": 1,
+                    "remainingAttempts": 6,
+                    "smsCount": 0,
+                    "status": 3,
+                    "statusText": "badCredentials"
+                }));
+        })
+        .await;
+
+    let err = setup.client.login_with_totp().await.unwrap_err();
+
+    totp_mock.assert();
+    assert!(matches!(
+        err,
+        DegiroError::BadCredentials {
+            remaining_attempts: Some(6),
+            login_failures: Some(1),
+            captcha_required: false,
+        }
+    ));
+}
+
+#[tokio::test]
+async fn login_bad_credentials_with_captcha() {
+    let setup = TestSetup::new().await;
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login/totp");
+            then.status(400)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": true,
+                    "loginFailures": 3,
+                    "remainingAttempts": 4,
+                    "status": 3,
+                    "statusText": "badCredentials"
+                }));
+        })
+        .await;
+
+    let err = setup.client.login_with_totp().await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        DegiroError::BadCredentials {
+            remaining_attempts: Some(4),
+            login_failures: Some(3),
+            captcha_required: true,
+        }
+    ));
+}
+
+#[tokio::test]
+async fn login_falls_back_to_totp() {
+    let setup = TestSetup::new().await;
+
+    let login_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login");
+            then.status(202)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "isPassCodeEnabled": false,
+                    "locale": "en_US",
+                    "status": 6,
+                    "statusText": "totpNeeded"
+                }));
+        })
+        .await;
+
+    let totp_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login/totp");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "isPassCodeEnabled": true,
+                    "locale": "en_US",
+                    "redirectUrl": "https://trader.degiro.nl/trader/",
+                    "sessionId": "mock-session-123",
+                    "status": 0,
+                    "statusText": "success",
+                    "userTokens": []
+                }));
+        })
+        .await;
+
+    let config_mock = mock_client_config(&setup.server).await;
+
+    let client_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "intAccount": 12345678,
+                        "username": "testuser",
+                        "email": "testuser@example.com"
+                    }
+                }));
+        })
+        .await;
+
+    setup.client.login().await.unwrap();
+
+    login_mock.assert();
+    totp_mock.assert();
+    client_mock.assert();
+    config_mock.assert();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn login_with_command_credentials() {
+    let server = MockServer::start_async().await;
+    let client = DegiroClient::builder()
+        .credentials(
+            CommandCredentials::new(DEGIRO_USERNAME, ["echo", "from-command"])
+                .with_totp_secret_command(["echo", DEGIRO_TOTP_SECRET]),
+        )
+        .base_url(server.base_url())
+        .finalize();
+
+    let totp_mock = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/login/secure/login/totp")
+                .body_contains(r#""password":"from-command""#);
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "sessionId": "mock-session-123",
+                    "status": 0,
+                    "statusText": "success"
+                }));
+        })
+        .await;
+
+    mock_client_config(&server).await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "intAccount": 12345678,
+                        "username": "testuser",
+                        "email": "testuser@example.com"
+                    }
+                }));
+        })
+        .await;
+
+    client.login_with_totp().await.unwrap();
+    totp_mock.assert();
+}
+
+#[tokio::test]
+async fn login_waits_for_in_app_confirmation() {
+    let server = MockServer::start_async().await;
+    let client = DegiroClient::builder()
+        .credentials(Credentials::new(DEGIRO_USERNAME, DEGIRO_PASSWORD))
+        .in_app_confirmation_timeout(Duration::from_secs(10))
+        .base_url(server.base_url())
+        .finalize();
+
+    let login_mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login");
+            then.status(202)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "inAppToken": "mock-in-app-token",
+                    "status": 12,
+                    "statusText": "inAppTOTPNeeded"
+                }));
+        })
+        .await;
+
+    let in_app_mock = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/login/secure/login/in-app")
+                .json_body_partial(r#"{"inAppToken": "mock-in-app-token"}"#);
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "sessionId": "mock-session-123",
+                    "status": 0,
+                    "statusText": "success"
+                }));
+        })
+        .await;
+
+    let config_mock = mock_client_config(&server).await;
+
+    let client_mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "intAccount": 12345678,
+                        "username": "testuser",
+                        "email": "testuser@example.com"
+                    }
+                }));
+        })
+        .await;
+
+    client.login().await.unwrap();
+
+    login_mock.assert();
+    in_app_mock.assert();
+    client_mock.assert();
+    config_mock.assert();
+}
+
+#[tokio::test]
+async fn resume_session_skips_login() {
+    let setup = TestSetup::new().await;
+
+    let login_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login");
+            then.status(500);
+        })
+        .await;
+
+    let config_mock = mock_client_config(&setup.server).await;
+
+    let client_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "stored-session-123")
+                .cookie("JSESSIONID", "stored-cookie");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "intAccount": 12345678,
+                        "username": "testuser",
+                        "email": "testuser@example.com"
+                    }
+                }));
+        })
+        .await;
+
+    let state: SessionState = serde_json::from_value(json!({
+        "sessionId": "stored-session-123",
+        "intAccount": 12345678,
+        "cookies": ["JSESSIONID=stored-cookie"],
+        "loggedInAt": "2025-06-01T08:00:00Z"
+    }))
+    .unwrap();
+
+    setup.client.resume_or_login(state).await.unwrap();
+
+    login_mock.assert_hits(0);
+    client_mock.assert();
+    config_mock.assert();
+
+    let exported = setup.client.session_state().unwrap();
+    assert_eq!(exported.session_id, "stored-session-123");
+    assert_eq!(
+        exported.cookies,
+        vec!["JSESSIONID=stored-cookie".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn resume_session_keeps_failures_of_the_probe() {
+    let setup = TestSetup::new().await;
+
+    let login_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path_contains("/login/secure/login");
+            then.status(500);
+        })
+        .await;
+
+    let client_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET).path("/pa/secure/client");
+            then.status(503);
+        })
+        .await;
+
+    let err = setup
+        .client
+        .resume_or_login(SessionState {
+            session_id: "stored-session-123".to_string(),
+            int_account: 12345678,
+            cookies: vec![],
+            logged_in_at: "2025-06-01T08:00:00Z".parse().unwrap(),
+        })
+        .await
+        .unwrap_err();
+
+    client_mock.assert();
+    login_mock.assert_hits(0);
+    assert!(matches!(
+        err,
+        DegiroError::HttpError { status, .. } if status.as_u16() == 503
+    ));
+}
+
+#[tokio::test]
+async fn keep_alive_reports_lost_session() {
+    let server = MockServer::start_async().await;
+    let client = DegiroClient::builder()
+        .credentials(Credentials::new(DEGIRO_USERNAME, DEGIRO_PASSWORD))
+        .relogin_policy(ReloginPolicy::Never)
+        .base_url(server.base_url())
+        .finalize();
+
+    client
+        .restore_session(SessionState {
+            session_id: "expired-session-123".to_string(),
+            int_account: 12345678,
+            cookies: vec![],
+            logged_in_at: "2025-06-01T08:00:00Z".parse().unwrap(),
+        })
+        .unwrap();
+
+    let client_mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "expired-session-123");
+            then.status(401);
+        })
+        .await;
+
+    let mut keep_alive = client.keep_alive(Duration::from_millis(10));
+    let err = keep_alive.session_lost().await.unwrap();
+
+    client_mock.assert();
+    assert!(matches!(err, DegiroError::SessionExpired));
+}
+
+#[tokio::test]
+async fn expired_session_is_renewed() {
+    let setup = TestSetup::new().await;
+
+    setup
+        .client
+        .restore_session(SessionState {
+            session_id: "expired-session-123".to_string(),
+            int_account: 12345678,
+            cookies: vec![],
+            logged_in_at: "2025-06-01T08:00:00Z".parse().unwrap(),
+        })
+        .unwrap();
+
+    let expired_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/account/info/12345678;jsessionid=expired-session-123");
+            then.status(401);
+        })
+        .await;
+
+    let login_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "sessionId": "mock-session-123",
+                    "status": 0,
+                    "statusText": "success"
+                }));
+        })
+        .await;
+
+    let config_mock = mock_client_config(&setup.server).await;
+
+    let client_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "intAccount": 12345678,
+                        "username": "testuser",
+                        "email": "testuser@example.com"
+                    }
+                }));
+        })
+        .await;
+
+    let account_info_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/account/info/12345678;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "baseCurrency": "EUR",
+                        "cashFunds": {},
+                        "clientId": 1234567,
+                        "currencyPairs": {},
+                        "marginType": "NO_MARGIN"
+                    }
+                }));
+        })
+        .await;
+
+    let info = setup.client.get_account_info().await.unwrap();
+
+    expired_mock.assert();
+    login_mock.assert();
+    config_mock.assert();
+    client_mock.assert();
+    account_info_mock.assert();
+    assert_eq!(info.base_currency, "EUR");
+    assert_eq!(
+        setup.client.client_config().unwrap().client_id,
+        Some(1234567)
+    );
+}
+
+#[tokio::test]
+async fn expired_session_does_not_replay_order_confirmation() {
+    let setup = TestSetup::logged_in().await;
+
+    let confirm_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/trading/secure/v5/order/c0ffee00;jsessionid=mock-session-123");
+            then.status(401);
+        })
+        .await;
+
+    let login_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path("/login/secure/login");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "captchaRequired": false,
+                    "sessionId": "renewed-session-456",
+                    "status": 0,
+                    "statusText": "success"
+                }));
+        })
+        .await;
+
+    mock_client_config(&setup.server).await;
+
+    let client_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "renewed-session-456");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "intAccount": 12345678,
+                        "username": "testuser",
+                        "email": "testuser@example.com"
+                    }
+                }));
+        })
+        .await;
+
+    let replay_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/trading/secure/v5/order/c0ffee00;jsessionid=renewed-session-456");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "data": { "orderId": "placed-twice" } }));
+        })
+        .await;
+
+    let order = Order {
+        buy_sell: OrderAction::Buy,
+        order_type: OrderType::Limit,
+        product_id: "1819819".to_string(),
+        size: 3.0,
+        price: Some(12.5),
+        time_type: OrderTimeType::GoodTillDay,
+        stop_price: None,
+        amount: None,
+    };
+
+    let result = setup.client.confirm_order("c0ffee00", &order).await;
+
+    assert!(matches!(result, Err(DegiroError::SessionExpired)));
+    confirm_mock.assert_hits(1);
+    login_mock.assert();
+    client_mock.assert();
+    assert_eq!(replay_mock.hits(), 0);
+    assert_eq!(
+        setup.client.session_state().unwrap().session_id,
+        "renewed-session-456"
+    );
+}
+
+#[tokio::test]
+async fn base_url_with_trailing_slash() {
+    let server = MockServer::start_async().await;
+    let client = DegiroClient::builder()
+        .credentials(Credentials::new(DEGIRO_USERNAME, DEGIRO_PASSWORD))
+        .base_url(format!("{}/", server.base_url()))
+        .finalize();
+    restore_mock_session(&client);
+
+    let account_info_mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/account/info/12345678;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "baseCurrency": "EUR",
+                        "cashFunds": {},
+                        "clientId": 1234567,
+                        "currencyPairs": {},
+                        "marginType": "NO_MARGIN"
+                    }
+                }));
+        })
+        .await;
+
+    client.get_account_info().await.unwrap();
+    account_info_mock.assert();
+}
+
+#[tokio::test]
+async fn client_profile() {
+    let setup = TestSetup::logged_in().await;
+
+    let client_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/pa/secure/client")
+                .query_param("sessionId", "mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "id": 1234567,
+                        "intAccount": 12345678,
+                        "loggedInPersonId": 7654321,
+                        "clientRole": "basic",
+                        "effectiveClientRole": "basic",
+                        "contractType": "PRIVATE",
+                        "username": "testuser",
+                        "displayName": "Test User",
+                        "email": "testuser@example.com",
+                        "firstContact": {
+                            "firstName": "Test",
+                            "lastName": "User",
+                            "nationality": "NL"
+                        },
+                        "address": {
+                            "streetAddress": "Teststraat",
+                            "streetAddressNumber": "1",
+                            "zip": "1234AB",
+                            "city": "Amsterdam",
+                            "country": "NL"
+                        },
+                        "language": "nl",
+                        "bankAccount": {
+                            "bankAccountId": 42,
+                            "bic": "TESTNL2A",
+                            "iban": "NL00TEST0123456789",
+                            "status": "VERIFIED"
+                        },
+                        "flatexBankAccount": {
+                            "bic": "BIWBDE33",
+                            "iban": "DE00TEST0123456789"
+                        },
+                        "memberCode": "TEST",
+                        "tradingProfile": "ACTIVE"
+                    }
+                }));
+        })
+        .await;
+
+    let profile = setup.client.get_client_profile().await.unwrap();
+
+    client_mock.assert();
+    assert_eq!(profile.id, Some(1234567));
+    assert_eq!(profile.contract_type.as_deref(), Some("PRIVATE"));
+    assert!(profile.is_flatex());
+    assert_eq!(profile.trading_profile.as_deref(), Some("ACTIVE"));
+    assert!(!profile.extra.contains_key("tradingProfile"));
+    assert_eq!(
+        profile.first_contact.unwrap().nationality.as_deref(),
+        Some("NL")
+    );
+    assert_eq!(profile.address.unwrap().city.as_deref(), Some("Amsterdam"));
+}
+
+#[test]
+fn credentials_are_redacted() {
+    let client = DegiroClient::builder()
+        .credentials(|| Ok(Credentials::new("testuser", "hunter2").with_totp_secret("SECRET")))
+        .finalize();
+
+    let debug = format!("{client:?}");
+    assert!(!debug.contains("hunter2"));
+    assert!(!debug.contains("SECRET"));
+
+    let credentials = Credentials::new("testuser", "hunter2").with_totp_secret("SECRET");
+    let debug = format!("{credentials:?}");
+    assert!(debug.contains("testuser"));
+    assert!(!debug.contains("hunter2"));
+    assert!(!debug.contains("SECRET"));
+}
+
+#[test]
+fn positions_from_portfolio() {
+    let portfolio: PortfolioResponse = serde_json::from_value(json!({
+        "portfolio": {
+            "lastUpdated": 42,
+            "name": "portfolio",
+            "isAdded": true,
+            "value": [
+                {
+                    "name": "positionrow",
+                    "id": "1819819",
+                    "isAdded": true,
+                    "value": [
+                        { "name": "id", "value": "1819819", "isAdded": true },
+                        { "name": "positionType", "value": "PRODUCT", "isAdded": true },
+                        { "name": "size", "value": 10, "isAdded": true },
+                        { "name": "price", "value": 12.5, "isAdded": true },
+                        { "name": "value", "value": 125.0, "isAdded": true },
+                        { "name": "plBase", "value": { "EUR": -100.0 }, "isAdded": true },
+                        { "name": "todayPlBase", "value": { "EUR": -120.0 }, "isAdded": true },
+                        { "name": "portfolioValueCorrection", "value": 0, "isAdded": true },
+                        { "name": "breakEvenPrice", "value": 10.0, "isAdded": true },
+                        { "name": "averageFxRate", "value": 1, "isAdded": true },
+                        { "name": "realizedProductPl", "value": 3.5, "isAdded": true },
+                        { "name": "realizedFxPl", "value": 0, "isAdded": true },
+                        { "name": "todayRealizedProductPl", "value": 0, "isAdded": true },
+                        { "name": "todayRealizedFxPl", "value": 0, "isAdded": true }
+                    ]
+                },
+                {
+                    "name": "positionrow",
+                    "id": "EUR",
+                    "isAdded": true,
+                    "value": [
+                        { "name": "id", "value": "EUR", "isAdded": true },
+                        { "name": "positionType", "value": "CASH", "isAdded": true },
+                        { "name": "size", "value": 250.75, "isAdded": true },
+                        { "name": "price", "value": 1, "isAdded": true },
+                        { "name": "value", "value": 250.75, "isAdded": true }
+                    ]
+                }
+            ]
+        }
+    }))
+    .unwrap();
+
+    let positions = portfolio.positions();
+    assert_eq!(positions.len(), 2);
+
+    let product = &positions[0];
+    assert_eq!(product.product_id, "1819819");
+    assert_eq!(product.position_type, PositionType::Product);
+    assert_eq!(product.size, 10.0);
+    assert_eq!(product.break_even_price, Some(10.0));
+    assert_eq!(product.pl_base["EUR"], -100.0);
+    assert_eq!(product.today_pl_base["EUR"], -120.0);
+    assert_eq!(product.unrealized_pl(), Some(25.0));
+
+    // 2 contracts of 100 options each
+    let option = Position {
+        size: 2.0,
+        price: 1.5,
+        value: 300.0,
+        break_even_price: Some(1.0),
+        ..product.clone()
+    };
+    assert_eq!(option.unrealized_pl(), Some(100.0));
+    assert_eq!(
+        product.extra.get("portfolioValueCorrection"),
+        Some(&ValueField::Number(0.0))
+    );
+
+    let cash = &positions[1];
+    assert_eq!(cash.position_type, PositionType::Cash);
+    assert_eq!(cash.value, 250.75);
+}
+
+#[tokio::test]
+async fn portfolio_tracker_applies_deltas() {
+    let setup = TestSetup::logged_in().await;
+
+    let update_path = "/trading/secure/v5/update/12345678;jsessionid=mock-session-123";
+    let full_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path(update_path)
+                .query_param("portfolio", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "portfolio": {
+                        "lastUpdated": 42,
+                        "name": "portfolio",
+                        "isAdded": true,
+                        "value": [
+                            {
+                                "name": "positionrow",
+                                "id": "1819819",
+                                "isAdded": true,
+                                "value": [
+                                    { "name": "positionType", "value": "PRODUCT", "isAdded": true },
+                                    { "name": "size", "value": 10, "isAdded": true },
+                                    { "name": "price", "value": 12.5, "isAdded": true }
+                                ]
+                            },
+                            {
+                                "name": "positionrow",
+                                "id": "332111",
+                                "isAdded": true,
+                                "value": [
+                                    { "name": "positionType", "value": "PRODUCT", "isAdded": true },
+                                    { "name": "size", "value": 5, "isAdded": true },
+                                    { "name": "price", "value": 100, "isAdded": true }
+                                ]
+                            }
+                        ]
+                    }
+                }));
+        })
+        .await;
+
+    let delta_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path(update_path)
+                .query_param("portfolio", "42");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "portfolio": {
+                        "lastUpdated": 43,
+                        "name": "portfolio",
+                        "isAdded": false,
+                        "value": [
+                            {
+                                "name": "positionrow",
+                                "id": "1819819",
+                                "isAdded": false,
+                                "value": [
+                                    { "name": "price", "value": 13.0, "isAdded": false }
+                                ]
+                            },
+                            {
+                                "name": "positionrow",
+                                "id": "332111",
+                                "isRemoved": true
+                            }
+                        ]
+                    }
+                }));
+        })
+        .await;
+
+    let mut tracker = setup.client.portfolio_tracker();
+
+    let diff = tracker.poll().await.unwrap();
+    full_mock.assert();
+    assert_eq!(diff.added.len(), 2);
+    assert_eq!(tracker.last_updated(), 42);
+
+    let diff = tracker.poll().await.unwrap();
+    delta_mock.assert();
+    assert!(diff.added.is_empty());
+    assert_eq!(diff.removed, vec!["332111".to_string()]);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].price, 13.0);
+    assert_eq!(diff.changed[0].size, 10.0);
+
+    let positions = tracker.positions();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(tracker.last_updated(), 43);
+}
+
+#[tokio::test]
+async fn open_orders() {
+    let setup = TestSetup::logged_in().await;
+
+    let orders_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/update/12345678;jsessionid=mock-session-123")
+                .query_param("orders", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(open_orders_body(OrderType::Limit, true));
+        })
+        .await;
+
+    let orders = setup.client.get_open_orders().await.unwrap();
+    orders_mock.assert();
+
+    assert_eq!(orders.len(), 1);
+    let order = &orders[0];
+    assert_eq!(order.order_id, "8a6b2c3e-0000-4d1f-9b7a-000000000001");
+    assert_eq!(order.product_id, "1819819");
+    assert_eq!(order.action, OrderAction::Buy);
+    assert_eq!(order.order_type, OrderType::Limit);
+    assert_eq!(order.time_type, OrderTimeType::GoodTillCanceled);
+    assert_eq!(order.size, 3.0);
+    assert_eq!(order.price, 11.5);
+    assert_eq!(order.stop_price, None);
+    assert_eq!(order.quantity_filled, Some(3.0));
+    assert_eq!(
+        order.created.map(|created| created.time()),
+        Some(jiff::civil::time(9, 12, 0, 0))
+    );
+    assert!(order.is_modifiable && order.is_deletable);
+}
+
+fn open_orders_body(order_type: OrderType, is_modifiable: bool) -> serde_json::Value {
+    json!({
+        "orders": {
+            "lastUpdated": 7,
+            "name": "orders",
+            "value": [
+                {
+                    "name": "order",
+                    "id": "8a6b2c3e-0000-4d1f-9b7a-000000000001",
+                    "isAdded": true,
+                    "value": [
+                        { "name": "id", "value": "8a6b2c3e-0000-4d1f-9b7a-000000000001", "isAdded": true },
+                        { "name": "date", "value": "09:12", "isAdded": true },
+                        { "name": "productId", "value": 1819819, "isAdded": true },
+                        { "name": "buysell", "value": "B", "isAdded": true },
+                        { "name": "size", "value": 3, "isAdded": true },
+                        { "name": "quantity", "value": 3, "isAdded": true },
+                        { "name": "price", "value": 11.5, "isAdded": true },
+                        { "name": "stopPrice", "value": 0, "isAdded": true },
+                        { "name": "orderTypeId", "value": order_type as i32, "isAdded": true },
+                        { "name": "orderTimeTypeId", "value": 3, "isAdded": true },
+                        { "name": "isModifiable", "value": is_modifiable, "isAdded": true },
+                        { "name": "isDeletable", "value": true, "isAdded": true }
+                    ]
+                }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn modify_and_cancel_order() {
+    let setup = TestSetup::logged_in().await;
+
+    let order_id = "8a6b2c3e-0000-4d1f-9b7a-000000000001";
+    let order_path = format!("/trading/secure/v5/order/{order_id};jsessionid=mock-session-123");
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/update/12345678;jsessionid=mock-session-123")
+                .query_param("orders", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(open_orders_body(OrderType::Limit, true));
+        })
+        .await;
+
+    let modify_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(PUT)
+                .path(&order_path)
+                .query_param("intAccount", "12345678")
+                .json_body(json!({
+                    "buySell": "BUY",
+                    "orderType": 0,
+                    "productId": "1819819",
+                    "size": 3.0,
+                    "price": 11.0,
+                    "timeType": 3
+                }));
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "data": { "responseDatetime": "2025-06-01T09:15:00" } }));
+        })
+        .await;
+
+    let changes = OrderChanges {
+        price: Some(11.0),
+        ..Default::default()
+    };
+    let update = setup.client.modify_order(order_id, &changes).await.unwrap();
+    modify_mock.assert();
+    assert_eq!(update.order_id, order_id);
+    assert!(update.response_datetime.is_some());
+
+    let result = setup.client.modify_order("already-filled", &changes).await;
+    assert!(matches!(result, Err(DegiroError::OrderNotModifiable(id)) if id == "already-filled"));
+
+    let cancel_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(DELETE).path(&order_path);
+            then.status(400)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "errors": [{ "text": "Order already executed" }] }));
+        })
+        .await;
+
+    let result = setup.client.cancel_order(order_id).await;
+    cancel_mock.assert();
+    match result {
+        Err(DegiroError::OrderRejected {
+            order_id: id,
+            reasons,
+        }) => {
+            assert_eq!(id, order_id);
+            assert_eq!(reasons, vec!["Order already executed".to_string()]);
+        }
+        other => panic!("expected a rejected order, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn modify_order_refuses_trailing_stops() {
+    let setup = TestSetup::logged_in().await;
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/update/12345678;jsessionid=mock-session-123")
+                .query_param("orders", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(open_orders_body(OrderType::TrailingStop, true));
+        })
+        .await;
+
+    let modify_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(PUT).path_contains("/trading/secure/v5/order/");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "data": {} }));
+        })
+        .await;
+
+    let changes = OrderChanges {
+        price: Some(11.0),
+        ..Default::default()
+    };
+    let result = setup
+        .client
+        .modify_order("8a6b2c3e-0000-4d1f-9b7a-000000000001", &changes)
+        .await;
+
+    assert!(matches!(result, Err(DegiroError::OrderNotModifiable(_))));
+    assert_eq!(modify_mock.hits(), 0);
+}
+
+#[tokio::test]
+async fn place_order_needs_approval() {
+    let setup = TestSetup::logged_in().await;
+
+    let check_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/trading/secure/v5/checkOrder;jsessionid=mock-session-123")
+                .query_param("intAccount", "12345678");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "confirmationId": "c0ffee00-0000-4000-8000-000000000001",
+                        "freeSpaceNew": 950.0,
+                        "transactionFee": 2.0
+                    }
+                }));
+        })
+        .await;
+
+    let confirm_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST).path(
+                "/trading/secure/v5/order/c0ffee00-0000-4000-8000-000000000001;jsessionid=mock-session-123",
+            );
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "data": { "orderId": "8a6b2c3e-0000-4d1f-9b7a-000000000001" } }));
+        })
+        .await;
+
+    let order = Order {
+        buy_sell: OrderAction::Buy,
+        order_type: OrderType::Limit,
+        product_id: "1819819".to_string(),
+        size: 3.0,
+        price: Some(11.5),
+        time_type: OrderTimeType::GoodTillDay,
+        stop_price: None,
+        amount: None,
+    };
+
+    let result = setup
+        .client
+        .place_order(&order, |check| check.transaction_fee < Some(1.0))
+        .await;
+    assert!(matches!(result, Err(DegiroError::OrderNotApproved { .. })));
+    assert_eq!(confirm_mock.hits(), 0);
+
+    let placed = setup
+        .client
+        .place_order(&order, |check| check.transaction_fee <= Some(2.0))
+        .await
+        .unwrap();
+    check_mock.assert_hits(2);
+    confirm_mock.assert();
+    assert_eq!(placed.order_id, "8a6b2c3e-0000-4d1f-9b7a-000000000001");
+    assert_eq!(placed.check.free_space_new, Some(950.0));
+}
+
+#[test]
+fn order_builder_validates_against_product() {
+    let product: ProductInfo = serde_json::from_value(json!({
+        "id": "1819819",
+        "name": "Test Corp",
+        "symbol": "TST",
+        "currency": "EUR",
+        "contractSize": 1.0,
+        "closePrice": 12.0,
+        "productTypeId": 1,
+        "tradable": true,
+        "orderTimeTypes": ["DAY", "GTC"],
+        "buyOrderTypes": ["LIMIT", "MARKET", "STOPLOSS", "STOPLIMIT"],
+        "sellOrderTypes": ["LIMIT", "MARKET"]
+    }))
+    .unwrap();
+
+    let order = Order::builder(&product, OrderAction::Buy)
+        .order_type(OrderType::StopLimit)
+        .size(10.0)
+        .price(12.5)
+        .stop_price(12.0)
+        .time_type(OrderTimeType::GoodTillCanceled)
+        .build()
+        .unwrap();
+    assert_eq!(order.product_id, "1819819");
+    assert_eq!(order.price, Some(12.5));
+
+    let market_with_price = Order::builder(&product, OrderAction::Buy)
+        .order_type(OrderType::Market)
+        .size(10.0)
+        .price(12.5)
+        .build();
+    assert!(matches!(
+        market_with_price,
+        Err(DegiroError::UnexpectedOrderField { field: "price", .. })
+    ));
+
+    let stop_limit_without_stop = Order::builder(&product, OrderAction::Buy)
+        .order_type(OrderType::StopLimit)
+        .size(10.0)
+        .price(12.5)
+        .build();
+    assert!(matches!(
+        stop_limit_without_stop,
+        Err(DegiroError::MissingOrderField {
+            field: "stop price",
+            ..
+        })
+    ));
+
+    let sell_stop_loss = Order::builder(&product, OrderAction::Sell)
+        .order_type(OrderType::StopLoss)
+        .size(10.0)
+        .stop_price(11.0)
+        .build();
+    assert!(matches!(
+        sell_stop_loss,
+        Err(DegiroError::OrderTypeNotSupported {
+            action: OrderAction::Sell,
+            ..
+        })
+    ));
+
+    let empty = Order::builder(&product, OrderAction::Buy)
+        .order_type(OrderType::Market)
+        .size(0.0)
+        .build();
+    assert!(matches!(
+        empty,
+        Err(DegiroError::InvalidOrderValue { field: "size", .. })
+    ));
+
+    // Sizes are in contracts, whatever the contract size
+    let option: ProductInfo = serde_json::from_value(json!({
+        "id": "4700000", "name": "Test Call", "symbol": "TC", "currency": "EUR",
+        "contractSize": 100.0, "closePrice": 1.5, "productTypeId": 8, "tradable": true
+    }))
+    .unwrap();
+    let one_contract = Order::builder(&option, OrderAction::Buy)
+        .order_type(OrderType::Limit)
+        .size(1.0)
+        .price(1.5)
+        .build()
+        .unwrap();
+    assert_eq!(one_contract.size, 1.0);
+    assert_eq!(one_contract.notional(&option), Some(150.0));
+
+    let untradable: ProductInfo = serde_json::from_value(json!({
+        "id": "1", "name": "Delisted", "symbol": "DL", "currency": "EUR",
+        "contractSize": 1.0, "closePrice": 0.0, "productTypeId": 1, "tradable": false
+    }))
+    .unwrap();
+    let result = Order::builder(&untradable, OrderAction::Buy)
+        .order_type(OrderType::Market)
+        .size(1.0)
+        .build();
+    assert!(matches!(result, Err(DegiroError::ProductNotTradable(id)) if id == "1"));
+}
+
+#[test]
+fn order_types_round_trip() {
+    let product: ProductInfo = serde_json::from_value(json!({
+        "id": "1819819", "name": "Test Corp", "symbol": "TST", "currency": "EUR",
+        "contractSize": 1.0, "closePrice": 12.0, "productTypeId": 1, "tradable": true,
+        "sellOrderTypes": ["LIMIT", "MARKET", "TRAILINGSTOP"]
+    }))
+    .unwrap();
+
+    let built = Order::builder(&product, OrderAction::Sell)
+        .order_type(OrderType::TrailingStop)
+        .size(10.0)
+        .build();
+    assert!(matches!(
+        built,
+        Err(DegiroError::OrderTypeNotBuildable(OrderType::TrailingStop))
+    ));
+
+    let fund: ProductInfo = serde_json::from_value(json!({
+        "id": "5000", "name": "Test Fund", "symbol": "FND", "currency": "EUR",
+        "contractSize": 1.0, "closePrice": 100.0, "productTypeId": 13, "tradable": true
+    }))
+    .unwrap();
+    let by_amount = Order::builder(&fund, OrderAction::Buy)
+        .order_type(OrderType::StandardAmount)
+        .amount(250.0)
+        .build()
+        .unwrap();
+    assert_eq!(by_amount.amount, Some(250.0));
+    assert_eq!(
+        serde_json::from_value::<Order>(serde_json::to_value(&by_amount).unwrap()).unwrap(),
+        by_amount
+    );
+}
+
+#[tokio::test]
+async fn dry_run_never_sends_orders() {
+    let server = MockServer::start_async().await;
+
+    let client = DegiroClient::builder()
+        .credentials(Credentials::new(DEGIRO_USERNAME, DEGIRO_PASSWORD))
+        .dry_run(true)
+        .base_url(server.base_url())
+        .finalize();
+
+    restore_mock_session(&client);
+
+    let check_mock = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/trading/secure/v5/checkOrder;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": { "confirmationId": "c0ffee00", "transactionFee": 2.0 }
+                }));
+        })
+        .await;
+
+    let order_mock = server
+        .mock_async(|when, then| {
+            when.path_contains("/trading/secure/v5/order/");
+            then.status(200)
+                .json_body(json!({ "data": { "orderId": "real-order" } }));
+        })
+        .await;
+
+    let order = Order {
+        buy_sell: OrderAction::Buy,
+        order_type: OrderType::Market,
+        product_id: "1819819".to_string(),
+        size: 3.0,
+        price: None,
+        time_type: OrderTimeType::GoodTillDay,
+        stop_price: None,
+        amount: None,
+    };
+
+    let placed = client.place_order(&order, |_| true).await.unwrap();
+    check_mock.assert();
+    assert_eq!(placed.order_id, "dry-run-c0ffee00");
+    assert_eq!(placed.check.transaction_fee, Some(2.0));
+
+    let cancelled = client.cancel_order("8a6b2c3e").await.unwrap();
+    assert_eq!(cancelled.order_id, "8a6b2c3e");
+
+    assert_eq!(order_mock.hits(), 0);
+}
+
+#[tokio::test]
+async fn risk_limits_reject_orders() {
+    let server = MockServer::start_async().await;
+
+    let limited_client = |dry_run: bool| {
+        let client = DegiroClient::builder()
+            .credentials(Credentials::new(DEGIRO_USERNAME, DEGIRO_PASSWORD))
+            .dry_run(dry_run)
+            .risk_limits(RiskLimits {
+                max_order_notional: Some(1000.0),
+                max_daily_notional: Some(1500.0),
+                allowed_products: Some(
+                    ["1819819", "4700000", "5500000"].map(str::to_string).into(),
+                ),
+                max_position_size: Some(60.0),
+            })
+            .base_url(server.base_url())
+            .finalize();
+        restore_mock_session(&client);
+        client
+    };
+    let client = limited_client(false);
+
+    mock_products(
+        &server,
+        json!({
+            "1819819": {
+                "id": "1819819", "name": "Test Corp", "symbol": "TST", "currency": "EUR",
+                "contractSize": 1.0, "closePrice": 25.0, "productTypeId": 1, "tradable": true
+            },
+            "4700000": {
+                "id": "4700000", "name": "Test Corp Call", "symbol": "TSTC", "currency": "EUR",
+                "contractSize": 100.0, "closePrice": 1.5, "productTypeId": 8, "tradable": true
+            },
+            "5500000": {
+                "id": "5500000", "name": "US Corp", "symbol": "USC", "currency": "USD",
+                "contractSize": 1.0, "closePrice": 125.0, "productTypeId": 1, "tradable": true
+            }
+        }),
+    )
+    .await;
+    mock_account_info(&server, json!({ "EURUSD": { "id": 1, "price": "1.25" } })).await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/update/12345678;jsessionid=mock-session-123")
+                .query_param("portfolio", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "portfolio": {
+                        "lastUpdated": 1,
+                        "name": "portfolio",
+                        "isAdded": true,
+                        "value": [{
+                            "name": "positionrow",
+                            "id": "1819819",
+                            "value": [
+                                { "name": "positionType", "value": "PRODUCT" },
+                                { "name": "size", "value": 50 }
+                            ]
+                        }]
+                    }
+                }));
+        })
+        .await;
+
+    let limit_order = |product_id: &str, size: f64, price: f64| Order {
+        buy_sell: OrderAction::Buy,
+        order_type: OrderType::Limit,
+        product_id: product_id.to_string(),
+        size,
+        price: Some(price),
+        time_type: OrderTimeType::GoodTillDay,
+        stop_price: None,
+        amount: None,
+    };
+    let mock_confirm = |confirmation_id: &'static str, status: u16, body: &'static str| {
+        server.mock_async(move |when, then| {
+            when.method(POST).path(format!(
+                "/trading/secure/v5/order/{confirmation_id};jsessionid=mock-session-123"
+            ));
+            then.status(status)
+                .header("content-type", "application/json;charset=UTF-8")
+                .body(body);
+        })
+    };
+    mock_confirm("c0ffee00", 200, r#"{ "data": { "orderId": "8a6b2c3e" } }"#).await;
+    mock_confirm(
+        "rejected",
+        400,
+        r#"{ "errors": [{ "text": "Price too far" }] }"#,
+    )
+    .await;
+    mock_confirm("garbled", 200, "<html>").await;
+
+    let confirm = |order: Order| {
+        let client = client.clone();
+        async move { client.confirm_order("c0ffee00", &order).await }
+    };
+
+    let result = confirm(limit_order("332111", 1.0, 10.0)).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::ProductNotAllowed(_)))
+    ));
+
+    let result = confirm(limit_order("1819819", 5.0, 250.0)).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::OrderNotional { notional, .. })) if notional == 1250.0
+    ));
+
+    let result = confirm(limit_order("1819819", 20.0, 10.0)).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::PositionSize { size, .. })) if size == 70.0
+    ));
+
+    // 12 per share for 100 shares a contract
+    let result = confirm(limit_order("4700000", 1.0, 12.0)).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::OrderNotional { notional, .. })) if notional == 1200.0
+    ));
+
+    // 1375 USD is 1100 EUR
+    let result = confirm(limit_order("5500000", 11.0, 125.0)).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::OrderNotional { notional, .. })) if notional == 1100.0
+    ));
+
+    let by_amount = Order {
+        order_type: OrderType::StandardAmount,
+        size: 0.0,
+        price: None,
+        amount: Some(500.0),
+        ..limit_order("1819819", 0.0, 0.0)
+    };
+    let result = confirm(by_amount).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::PositionSize { size, .. })) if size == 70.0
+    ));
+
+    // Dry runs are held to the daily limit without counting towards it
+    let dry_run_client = limited_client(true);
+    for _ in 0..3 {
+        dry_run_client
+            .confirm_order("c0ffee00", &limit_order("1819819", 5.0, 180.0))
+            .await
+            .unwrap();
+    }
+
+    confirm(limit_order("1819819", 5.0, 180.0)).await.unwrap();
+    // Rejected by DEGIRO, so it doesn't count
+    let result = client
+        .confirm_order("rejected", &limit_order("1819819", 5.0, 100.0))
+        .await;
+    assert!(matches!(result, Err(DegiroError::HttpError { .. })));
+    // Might have gone through, so it does
+    let result = client
+        .confirm_order("garbled", &limit_order("1819819", 5.0, 100.0))
+        .await;
+    assert!(result.is_err());
+    let result = confirm(limit_order("1819819", 1.0, 120.0)).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::DailyNotional { total, .. })) if total == 1520.0
+    ));
+
+    client.engage_kill_switch();
+    let result = confirm(limit_order("1819819", 1.0, 10.0)).await;
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::KillSwitch))
+    ));
+
+    client.release_kill_switch();
+    confirm(limit_order("1819819", 1.0, 10.0)).await.unwrap();
+
+    // Modifications count with what they add to the open order of 3 at 11.5
+    server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/update/12345678;jsessionid=mock-session-123")
+                .query_param("orders", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(open_orders_body(OrderType::Limit, true));
+        })
+        .await;
+    let modify_mock = server
+        .mock_async(|when, then| {
+            when.method(PUT).path(
+                "/trading/secure/v5/order/8a6b2c3e-0000-4d1f-9b7a-000000000001;jsessionid=mock-session-123",
+            );
+            then.status(200);
+        })
+        .await;
+    let new_price = |price: f64| OrderChanges {
+        price: Some(price),
+        ..Default::default()
+    };
+    let order_id = "8a6b2c3e-0000-4d1f-9b7a-000000000001";
+    client
+        .modify_order(order_id, &new_price(30.0))
+        .await
+        .unwrap();
+    let result = client.modify_order(order_id, &new_price(50.0)).await;
+    modify_mock.assert_hits(1);
+    assert!(matches!(
+        result,
+        Err(DegiroError::RiskLimit(RiskRule::DailyNotional { total, .. })) if total == 1581.0
+    ));
+}
+
+#[tokio::test]
+async fn submit_orders_reports_per_order() {
+    let setup = TestSetup::logged_in().await;
+
+    let check_path = "/trading/secure/v5/checkOrder;jsessionid=mock-session-123";
+    let check_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path(check_path)
+                .json_body_partial(r#"{ "productId": "1819819" }"#);
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "confirmationId": "c0ffee00",
+                        "freeSpaceNew": 900.0,
+                        "transactionFee": 2.0
+                    }
+                }));
+        })
+        .await;
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path(check_path)
+                .json_body_partial(r#"{ "productId": "332111" }"#);
+            then.status(400)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "errors": [{ "text": "Product not tradable" }] }));
+        })
+        .await;
+    let confirm_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/trading/secure/v5/order/c0ffee00;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "data": { "orderId": "8a6b2c3e" } }));
+        })
+        .await;
+
+    mock_products(
+        &setup.server,
+        json!({
+            "1819819": {
+                "id": "1819819", "name": "Test Corp", "symbol": "TST", "currency": "EUR",
+                "contractSize": 1.0, "closePrice": 12.0, "productTypeId": 1, "tradable": true
+            }
+        }),
+    )
+    .await;
+
+    mock_account_info(&setup.server, json!({})).await;
+
+    let limit_order = |product_id: &str| Order {
+        buy_sell: OrderAction::Buy,
+        order_type: OrderType::Limit,
+        product_id: product_id.to_string(),
+        size: 4.0,
+        price: Some(12.0),
+        time_type: OrderTimeType::GoodTillDay,
+        stop_price: None,
+        amount: None,
+    };
+    let orders = || {
+        vec![
+            limit_order("1819819"),
+            limit_order("332111"),
+            limit_order("1819819"),
+        ]
+    };
+
+    let report = setup
+        .client
+        .submit_orders(orders(), &BatchOptions::default())
+        .await;
+    assert!(report.aborted);
+    assert_eq!(report.placed().count(), 0);
+    assert!(matches!(report.outcomes[0], OrderOutcome::NotConfirmed(_)));
+    assert!(matches!(report.outcomes[1], OrderOutcome::CheckFailed(_)));
+    assert_eq!(confirm_mock.hits(), 0);
+
+    let options = BatchOptions {
+        abort_on_failed_check: false,
+        confirm_interval: Duration::ZERO,
+    };
+    let report = setup.client.submit_orders(orders(), &options).await;
+    assert!(!report.aborted);
+    assert_eq!(report.placed().count(), 2);
+    assert_eq!(report.failed(), 1);
+    assert_eq!(report.total_fees, 4.0);
+    assert_eq!(report.required_cash, 100.0);
+    assert_eq!(report.available_cash, Some(950.0));
+    check_mock.assert_hits(4);
+    confirm_mock.assert_hits(2);
+}
+
+#[tokio::test]
+async fn submit_orders_counts_cash_in_base_currency() {
+    let setup = TestSetup::logged_in().await;
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/trading/secure/v5/checkOrder;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "confirmationId": "c0ffee00",
+                        "freeSpaceNew": 50.0,
+                        "transactionFee": 1.0
+                    }
+                }));
+        })
+        .await;
+    mock_products(
+        &setup.server,
+        json!({
+            "5500000": {
+                "id": "5500000", "name": "US Corp", "symbol": "USC", "currency": "USD",
+                "contractSize": 1.0, "closePrice": 12.0, "productTypeId": 1, "tradable": true
+            }
+        }),
+    )
+    .await;
+    mock_account_info(
+        &setup.server,
+        json!({ "EURUSD": { "id": 1, "price": "1.25" } }),
+    )
+    .await;
+
+    let order = Order {
+        buy_sell: OrderAction::Buy,
+        order_type: OrderType::Limit,
+        product_id: "5500000".to_string(),
+        size: 10.0,
+        price: Some(12.0),
+        time_type: OrderTimeType::GoodTillDay,
+        stop_price: None,
+        amount: None,
+    };
+    let options = BatchOptions {
+        abort_on_failed_check: true,
+        confirm_interval: Duration::ZERO,
+    };
+    let dry_run = DegiroClient::builder()
+        .credentials(Credentials::new(DEGIRO_USERNAME, DEGIRO_PASSWORD))
+        .dry_run(true)
+        .base_url(setup.server.base_url())
+        .finalize();
+    restore_mock_session(&dry_run);
+
+    let report = dry_run.submit_orders(vec![order], &options).await;
+    // 120 USD is 96 EUR, plus the fee
+    assert_eq!(report.required_cash, 97.0);
+    assert_eq!(report.available_cash, Some(147.0));
+    assert!(!report.aborted);
+}
+
+#[tokio::test]
+async fn total_portfolio_in_base_currency() {
+    let setup = TestSetup::logged_in().await;
+
+    let account_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/account/info/12345678;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "baseCurrency": "EUR",
+                        "cashFunds": {},
+                        "clientId": 1234567,
+                        "currencyPairs": {},
+                        "marginType": "NONE"
+                    }
+                }));
+        })
+        .await;
+
+    let update_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/update/12345678;jsessionid=mock-session-123")
+                .query_param("portfolio", "0")
+                .query_param("totalPortfolio", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "portfolio": {
+                        "lastUpdated": 42,
+                        "name": "portfolio",
+                        "isAdded": true,
+                        "value": [{
+                            "name": "positionrow",
+                            "id": "1819819",
+                            "value": [
+                                { "name": "positionType", "value": "PRODUCT" },
+                                { "name": "size", "value": 10 },
+                                { "name": "value", "value": 130.0 },
+                                { "name": "todayPlBase", "value": { "EUR": -125.0 } }
+                            ]
+                        }]
+                    },
+                    "totalPortfolio": {
+                        "lastUpdated": 42,
+                        "name": "totalPortfolio",
+                        "value": [
+                            { "name": "freeSpaceNew", "value": { "EUR": 870.5 }, "isAdded": true },
+                            { "name": "totalCash", "value": 870.5, "isAdded": true },
+                            { "name": "reportMargin", "value": 870.5, "isAdded": true },
+                            { "name": "reportPortfValue", "value": 130.0, "isAdded": true },
+                            { "name": "reportNetliq", "value": 1000.5, "isAdded": true },
+                            { "name": "degiroCash", "value": 870.5, "isAdded": true }
+                        ]
+                    }
+                }));
+        })
+        .await;
+
+    mock_products(
+        &setup.server,
+        json!({
+            "1819819": {
+                "id": "1819819", "name": "Test Corp", "symbol": "TST", "currency": "EUR",
+                "contractSize": 1.0, "closePrice": 13.0, "productTypeId": 1, "tradable": true
+            }
+        }),
+    )
+    .await;
+
+    let total = setup.client.get_total_portfolio().await.unwrap();
+    account_mock.assert();
+    update_mock.assert();
+
+    assert_eq!(total.base_currency, "EUR");
+    assert_eq!(total.free_space, Some(870.5));
+    assert_eq!(total.total_cash, Some(870.5));
+    assert_eq!(total.portfolio_value, Some(130.0));
+    assert_eq!(total.net_asset_value, Some(1000.5));
+    assert_eq!(total.today_pl, Some(5.0));
+    assert_eq!(total.extra["degiroCash"], ValueField::Number(870.5));
+}
+
+#[tokio::test]
+async fn total_portfolio_converts_foreign_positions() {
+    let setup = TestSetup::logged_in().await;
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/account/info/12345678;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "data": {
+                        "baseCurrency": "EUR",
+                        "cashFunds": {},
+                        "clientId": 1234567,
+                        "currencyPairs": {
+                            "EURUSD": { "id": 1, "price": "1.25" },
+                            "GBPEUR": { "id": 2, "price": "1.2" }
+                        },
+                        "marginType": "NONE"
+                    }
+                }));
+        })
+        .await;
+
+    let position = |id: &str, value: f64, today_pl_base: f64| {
+        json!({
+            "name": "positionrow",
+            "id": id,
+            "value": [
+                { "name": "positionType", "value": "PRODUCT" },
+                { "name": "size", "value": 10 },
+                { "name": "value", "value": value },
+                { "name": "todayPlBase", "value": { "EUR": today_pl_base } }
+            ]
+        })
+    };
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/trading/secure/v5/update/12345678;jsessionid=mock-session-123");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "portfolio": {
+                        "lastUpdated": 42,
+                        "name": "portfolio",
+                        "isAdded": true,
+                        "value": [
+                            // 250 USD is 200 EUR, worth 190 EUR this morning
+                            position("4700000", 250.0, -190.0),
+                            // 100 GBP is 120 EUR, worth 125 EUR this morning
+                            position("332111", 100.0, -125.0)
+                        ]
+                    },
+                    "totalPortfolio": {
+                        "lastUpdated": 42,
+                        "name": "totalPortfolio",
+                        "value": [{ "name": "totalCash", "value": 10.0, "isAdded": true }]
+                    }
+                }));
+        })
+        .await;
+
+    let products_mock = mock_products(
+        &setup.server,
+        json!({
+            "4700000": {
+                "id": "4700000", "name": "US Corp", "symbol": "USC", "currency": "USD",
+                "contractSize": 1.0, "closePrice": 25.0, "productTypeId": 1, "tradable": true
+            },
+            "332111": {
+                "id": "332111", "name": "UK Corp", "symbol": "UKC", "currency": "GBP",
+                "contractSize": 1.0, "closePrice": 10.0, "productTypeId": 1, "tradable": true
+            }
+        }),
+    )
+    .await;
+
+    let total = setup.client.get_total_portfolio().await.unwrap();
+    products_mock.assert();
+
+    assert_eq!(total.base_currency, "EUR");
+    let today_pl = total.today_pl.unwrap();
+    assert!((today_pl - 5.0).abs() < 1e-9, "{today_pl}");
+}
+
+#[tokio::test]
+async fn poll_update_multiple_sections() {
+    let setup = TestSetup::logged_in().await;
+
+    let update_path = "/trading/secure/v5/update/12345678;jsessionid=mock-session-123";
+    let full_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path(update_path)
+                .query_param("intAccount", "12345678")
+                .query_param("orders", "0")
+                .query_param("historicalOrders", "0")
+                .query_param("transactions", "0")
+                .query_param("cashFunds", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "orders": { "lastUpdated": 7, "name": "orders", "value": [] },
+                    "historicalOrders": {
+                        "lastUpdated": 3,
+                        "name": "historicalOrders",
+                        "value": [{
+                            "name": "order",
+                            "id": "8a6b2c3e",
+                            "value": [{ "name": "productId", "value": 1819819 }]
+                        }]
+                    },
+                    "transactions": {
+                        "lastUpdated": 5,
+                        "name": "transactions",
+                        "value": [{
+                            "name": "transaction",
+                            "id": "424242",
+                            "isAdded": true,
+                            "value": [
+                                { "name": "productId", "value": 1819819, "isAdded": true },
+                                { "name": "buysell", "value": "B", "isAdded": true },
+                                { "name": "date", "value": "2025-06-02T09:12:00", "isAdded": true },
+                                { "name": "price", "value": 12.5, "isAdded": true },
+                                { "name": "quantity", "value": 4, "isAdded": true },
+                                { "name": "total", "value": -50.0, "isAdded": true },
+                                { "name": "feeInBaseCurrency", "value": -2.0, "isAdded": true },
+                                { "name": "counterParty", "value": "MK", "isAdded": true }
+                            ]
+                        }]
+                    },
+                    "cashFunds": {
+                        "lastUpdated": 9,
+                        "name": "cashFunds",
+                        "value": [{
+                            "name": "cashFund",
+                            "id": "2",
+                            "value": [
+                                { "name": "id", "value": 2 },
+                                { "name": "currencyCode", "value": "EUR" },
+                                { "name": "value", "value": 1234.5 }
+                            ]
+                        }]
+                    }
+                }));
+        })
+        .await;
+
+    let next_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path(update_path)
+                .query_param("orders", "7")
+                .query_param("historicalOrders", "3")
+                .query_param("transactions", "5")
+                .query_param("cashFunds", "9");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({}));
+        })
+        .await;
+
+    let request = UpdateRequest {
+        orders: Some(0),
+        historical_orders: Some(0),
+        transactions: Some(0),
+        cash_funds: Some(0),
+        ..Default::default()
+    };
+    let response = setup.client.poll_update(&request).await.unwrap();
+    full_mock.assert();
+    assert!(response.portfolio.is_none());
+    assert!(response.orders.as_ref().unwrap().open_orders().is_empty());
+    let historical = response.historical_orders.as_ref().unwrap();
+    assert_eq!(
+        historical.value[0].field("productId"),
+        Some(&ValueField::Number(1819819.0))
+    );
+
+    let transactions = response.transactions.as_ref().unwrap().transactions();
+    assert_eq!(transactions.len(), 1);
+    let transaction = &transactions[0];
+    assert_eq!(transaction.id, "424242");
+    assert_eq!(transaction.product_id.as_deref(), Some("1819819"));
+    assert_eq!(transaction.action, Some(OrderAction::Buy));
+    assert_eq!(
+        transaction.date,
+        Some("2025-06-02T09:12:00".parse().unwrap())
+    );
+    assert_eq!(transaction.quantity, Some(4.0));
+    assert_eq!(transaction.fee_in_base_currency, Some(-2.0));
+    assert_eq!(
+        transaction.extra.get("counterParty"),
+        Some(&ValueField::String("MK".to_string()))
+    );
+
+    let cash_funds = response.cash_funds.as_ref().unwrap().cash_funds();
+    assert_eq!(cash_funds[0].currency.as_deref(), Some("EUR"));
+    assert_eq!(cash_funds[0].value, Some(1234.5));
+
+    let next = request.next(&response);
+    assert_eq!(next.historical_orders, Some(3));
+    let response = setup.client.poll_update(&next).await.unwrap();
+    next_mock.assert();
+    assert!(response.is_empty());
+    assert_eq!(next.next(&response), next);
+}
+
+#[tokio::test]
+async fn watch_streams_changes() {
+    let setup = TestSetup::logged_in().await;
+
+    let account_path = "/trading/secure/v5/account/info/12345678;jsessionid=mock-session-123";
+    let account_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET).path(account_path);
+            then.status(503);
+        })
+        .await;
+
+    mock_products(
+        &setup.server,
+        json!({
+            "1819819": {
+                "id": "1819819", "name": "Test Corp", "symbol": "TST", "currency": "EUR",
+                "contractSize": 1.0, "closePrice": 12.5, "productTypeId": 1, "tradable": true
+            }
+        }),
+    )
+    .await;
+
+    let order = |id: &str, product_id: u64, buysell: &str| {
+        json!({
+            "name": "order",
+            "id": id,
+            "isAdded": true,
+            "value": [
+                { "name": "productId", "value": product_id, "isAdded": true },
+                { "name": "buysell", "value": buysell, "isAdded": true },
+                { "name": "size", "value": 10, "isAdded": true },
+                { "name": "price", "value": 13.0, "isAdded": true },
+                { "name": "orderTypeId", "value": 0, "isAdded": true },
+                { "name": "orderTimeTypeId", "value": 1, "isAdded": true }
+            ]
+        })
+    };
+    let transaction = |id: &str, buysell: &str, quantity: i64| {
+        json!({
+            "name": "transaction",
+            "id": id,
+            "isAdded": true,
+            "value": [
+                { "name": "productId", "value": 1819819, "isAdded": true },
+                { "name": "buysell", "value": buysell, "isAdded": true },
+                { "name": "quantity", "value": quantity, "isAdded": true }
+            ]
+        })
+    };
+    let update_path = "/trading/secure/v5/update/12345678;jsessionid=mock-session-123";
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path(update_path)
+                .query_param("portfolio", "0")
+                .query_param("totalPortfolio", "0")
+                .query_param("orders", "0")
+                .query_param("transactions", "0");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "portfolio": {
+                        "lastUpdated": 10,
+                        "name": "portfolio",
+                        "isAdded": true,
+                        "value": [{
+                            "name": "positionrow",
+                            "id": "1819819",
+                            "isAdded": true,
+                            "value": [
+                                { "name": "positionType", "value": "PRODUCT", "isAdded": true },
+                                { "name": "size", "value": 10, "isAdded": true },
+                                { "name": "price", "value": 12.5, "isAdded": true }
+                            ]
+                        }]
+                    },
+                    "totalPortfolio": {
+                        "lastUpdated": 10,
+                        "name": "totalPortfolio",
+                        "value": [{ "name": "freeSpaceNew", "value": { "EUR": 500.0 }, "isAdded": true }]
+                    },
+                    "orders": {
+                        "lastUpdated": 10,
+                        "name": "orders",
+                        "value": [
+                            order("7c8d9e0f", 331868, "B"),
+                            order("8a6b2c3e", 1819819, "S"),
+                            order("9b7c3d4f", 1819819, "B")
+                        ]
+                    },
+                    // From before the watch, not a fill of the open orders
+                    "transactions": {
+                        "lastUpdated": 10,
+                        "name": "transactions",
+                        "value": [transaction("1", "B", 10)]
+                    }
+                }));
+        })
+        .await;
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path(update_path)
+                .query_param("portfolio", "10")
+                .query_param("orders", "10")
+                .query_param("transactions", "10");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "portfolio": {
+                        "lastUpdated": 11,
+                        "name": "portfolio",
+                        "isAdded": false,
+                        "value": [{
+                            "name": "positionrow",
+                            "id": "1819819",
+                            "value": [{ "name": "size", "value": 0 }]
+                        }]
+                    },
+                    "orders": {
+                        "lastUpdated": 11,
+                        "name": "orders",
+                        "value": [
+                            { "name": "order", "id": "7c8d9e0f", "isRemoved": true },
+                            { "name": "order", "id": "8a6b2c3e", "isRemoved": true }
+                        ]
+                    },
+                    "transactions": {
+                        "lastUpdated": 11,
+                        "name": "transactions",
+                        "value": [transaction("2", "S", -10), transaction("3", "B", 6)]
+                    }
+                }));
+        })
+        .await;
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path(update_path)
+                .query_param("portfolio", "11")
+                .query_param("orders", "11")
+                .query_param("transactions", "11");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({}));
+        })
+        .await;
+
+    let options = WatchOptions {
+        interval: Duration::from_millis(10),
+        ..Default::default()
+    };
+    let mut events = Box::pin(setup.client.watch(options));
+
+    // The total portfolio is built again on the next poll after a failure
+    assert!(matches!(
+        events.next().await,
+        Some(Err(DegiroError::HttpError { .. }))
+    ));
+    account_mock.delete_async().await;
+    mock_account_info(&setup.server, json!({})).await;
+
+    let mut next = async || events.next().await.unwrap().unwrap();
+    assert!(matches!(next().await, WatchEvent::PositionOpened(p) if p.size == 10.0));
+    assert!(matches!(next().await, WatchEvent::OrderCreated(o) if o.order_id == "7c8d9e0f"));
+    assert!(matches!(next().await, WatchEvent::OrderCreated(o) if o.order_id == "8a6b2c3e"));
+    assert!(matches!(next().await, WatchEvent::OrderCreated(o) if o.order_id == "9b7c3d4f"));
+    assert!(matches!(next().await, WatchEvent::PositionClosed(p) if p.size == 10.0));
+    assert!(matches!(next().await, WatchEvent::OrderFilled(o) if o.order_id == "8a6b2c3e"));
+    assert!(matches!(
+        next().await,
+        WatchEvent::OrderPartiallyFilled { order, filled } if order.order_id == "9b7c3d4f" && filled == 6.0
+    ));
+    assert!(matches!(next().await, WatchEvent::CashChanged(t) if t.free_space == Some(500.0)));
+    // Removed without a fill, reported once the next poll brought none either
+    assert!(matches!(next().await, WatchEvent::OrderCancelled(o) if o.order_id == "7c8d9e0f"));
+}
+
+#[tokio::test]
+async fn quotecast_streams_quotes() {
+    let setup = TestSetup::logged_in().await;
+    mock_client_config(&setup.server).await;
+
+    let session_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/vwd-quotecast-service/request_session")
+                .query_param("userToken", "1234567");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "sessionId": "vwd-session-1" }));
+        })
+        .await;
+
+    let subscribe_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/vwd-quotecast-service/vwd-session-1")
+                .body_contains("a_req(360015751.LastPrice);")
+                .body_contains("a_req(360015751.BidPrice);");
+            then.status(200);
+        })
+        .await;
+
+    let unsubscribe_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/vwd-quotecast-service/vwd-session-1")
+                .body_contains("a_rel(360015751.LastPrice);");
+            then.status(200);
+        })
+        .await;
+
+    setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/vwd-quotecast-service/vwd-session-1")
+                .query_param("userToken", "1234567");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!([
+                    { "m": "a_req", "v": ["360015751.LastPrice", 101] },
+                    { "m": "a_req", "v": ["360015751.BidPrice", 102] },
+                    { "m": "a_req", "v": ["360015751.LastTime", 103] },
+                    { "m": "un", "v": [101, 12.34] },
+                    { "m": "un", "v": [102, 12.3] },
+                    { "m": "us", "v": [103, "14:32:10"] },
+                    { "m": "h" }
+                ]));
+        })
+        .await;
+
+    let quotecast = setup.client.quotecast().await.unwrap();
+    session_mock.assert();
+
+    quotecast.subscribe(["360015751"]).await.unwrap();
+    subscribe_mock.assert();
+
+    let mut quotes = Box::pin(quotecast.quotes());
+    let quote = quotes.next().await.unwrap().unwrap();
+    assert_eq!(quote.vwd_id, "360015751");
+    assert_eq!(quote.last_price, Some(12.34));
+    assert_eq!(quote.bid_price, Some(12.3));
+    assert_eq!(quote.ask_price, None);
+    assert_eq!(quote.last_time.as_deref(), Some("14:32:10"));
+
+    quotecast.unsubscribe(["360015751"]).await.unwrap();
+    unsubscribe_mock.assert();
+}
+
+#[tokio::test]
+async fn price_history_returns_candles() {
+    let setup = TestSetup::logged_in().await;
+    mock_client_config(&setup.server).await;
+
+    let chart_mock = setup
+        .server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/hchart/v1/deGiro/data.js")
+                .query_param("series", "ohlc:issueid:360015751")
+                .query_param("period", "P1M")
+                .query_param("resolution", "P1D")
+                .query_param("userToken", "1234567");
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({
+                    "requestid": "1",
+                    "start": "2025-05-01T00:00:00",
+                    "end": "2025-05-31T23:59:59",
+                    "resolution": "P1D",
+                    "series": [{
+                        "id": "ohlc:issueid:360015751",
+                        "type": "ohlc",
+                        "times": "2025-05-01T00:00:00/P1D",
+                        "data": [
+                            [0, 10.0, 10.5, 9.8, 10.2],
+                            [1, 10.2, 10.9, 10.1, 10.8],
+                            [4, null, null, null, null],
+                            [5, 10.8, 11.0, 10.4, 10.6]
+                        ]
+                    }]
+                }));
+        })
+        .await;
+
+    let product: ProductInfo = serde_json::from_value(json!({
+        "id": "1819819",
+        "name": "Test Corp",
+        "symbol": "TST",
+        "currency": "EUR",
+        "contractSize": 1.0,
+        "closePrice": 12.0,
+        "productTypeId": 1,
+        "tradable": true,
+        "vwdIdentifierType": "issueid",
+        "vwdId": "360015751"
+    }))
+    .unwrap();
+
+    let candles = setup
+        .client
+        .get_price_history(&product, ChartPeriod::OneMonth, ChartResolution::OneDay)
+        .await
+        .unwrap();
+
+    chart_mock.assert();
+    assert_eq!(candles.len(), 3);
+    assert_eq!(candles[0].time, "2025-05-01T00:00:00Z".parse().unwrap());
+    assert_eq!(candles[0].open, 10.0);
+    assert_eq!(candles[1].time, "2025-05-02T00:00:00Z".parse().unwrap());
+    assert_eq!(candles[1].high, 10.9);
+    assert_eq!(candles[2].time, "2025-05-06T00:00:00Z".parse().unwrap());
+    assert_eq!(candles[2].close, 10.6);
+
+    let without_vwd_id: ProductInfo = serde_json::from_value(json!({
+        "id": "1819820",
+        "name": "Other Corp",
+        "symbol": "OTH",
+        "currency": "EUR",
+        "contractSize": 1.0,
+        "closePrice": 12.0,
+        "productTypeId": 1,
+        "tradable": true
+    }))
+    .unwrap();
+    let result = setup
+        .client
+        .get_price_history(
+            &without_vwd_id,
+            ChartPeriod::OneMonth,
+            ChartResolution::OneDay,
+        )
+        .await;
+    assert!(matches!(result, Err(DegiroError::MissingVwdId(id)) if id == "1819820"));
+}
+
+#[tokio::test]
+async fn price_history_without_series() {
+    let setup = TestSetup::logged_in().await;
+    mock_client_config(&setup.server).await;
+
+    let chart_mock = |vwd_id: &str, series: serde_json::Value| {
+        let series_id = format!("ohlc:issueid:{vwd_id}");
+        setup.server.mock_async(move |when, then| {
+            when.method(GET)
+                .path("/hchart/v1/deGiro/data.js")
+                .query_param("series", series_id);
+            then.status(200)
+                .header("content-type", "application/json;charset=UTF-8")
+                .json_body(json!({ "requestid": "1", "series": series }));
+        })
+    };
+    chart_mock("360015751", json!([])).await;
+    chart_mock(
+        "360015752",
+        json!([{ "id": "ohlc:issueid:360015752", "type": "ohlc", "data": [] }]),
+    )
+    .await;
+
+    let product = |vwd_id: &str| -> ProductInfo {
+        serde_json::from_value(json!({
+            "id": "1819819",
+            "name": "Test Corp",
+            "symbol": "TST",
+            "currency": "EUR",
+            "contractSize": 1.0,
+            "closePrice": 12.0,
+            "productTypeId": 1,
+            "tradable": true,
+            "vwdIdentifierType": "issueid",
+            "vwdId": vwd_id
+        }))
+        .unwrap()
+    };
+
+    let missing = setup
+        .client
+        .get_price_history(
+            &product("360015751"),
+            ChartPeriod::OneMonth,
+            ChartResolution::OneDay,
+        )
+        .await;
+    assert!(matches!(
+        missing,
+        Err(DegiroError::MissingChartSeries(id)) if id == "ohlc:issueid:360015751"
+    ));
+
+    let empty = setup
+        .client
+        .get_price_history(
+            &product("360015752"),
+            ChartPeriod::OneMonth,
+            ChartResolution::OneDay,
+        )
+        .await
+        .unwrap();
+    assert!(empty.is_empty());
 }